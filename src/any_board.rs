@@ -0,0 +1,234 @@
+use crate::board::{Parity, SudokuBoard};
+use crate::dlx::dlx_solver::DlxSolver;
+use crate::puzzle::{parse_puzzle, Given};
+use crate::region::RegionLayout;
+use crate::solver::adjacency::Adjacency;
+use crate::solver::killer_cage::Cage;
+use crate::solver::line::Line;
+use crate::solver::outside_clue::OutsideClue;
+use crate::solver::SudokuSolver;
+use crate::variant::VariantPuzzle;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Defines `AnyBoard` and `AnySolver`, one variant per supported size, and their dispatch
+/// from the runtime size to the const generic `SudokuBoard<N>` and `SudokuSolver<N>`.
+macro_rules! any_size {
+    ($($size:literal => $variant:ident),* $(,)?) => {
+        /// A `SudokuBoard` whose size is only known at runtime, like one read from a file.
+        #[derive(Clone)]
+        pub enum AnyBoard {
+            $($variant(Box<SudokuBoard<$size>>)),*
+        }
+
+        /// A `SudokuSolver` whose size is only known at runtime.
+        pub enum AnySolver {
+            $($variant(Box<SudokuSolver<$size>>)),*
+        }
+
+        impl AnyBoard {
+            pub fn with_layout(layout: RegionLayout) -> Result<Self, String> {
+                match layout.size() {
+                    $($size => Ok(Self::$variant(Box::new(SudokuBoard::with_layout(layout)))),)*
+                    size => Err(format!("Unsupported board size {size}.")),
+                }
+            }
+
+            pub const fn size(&self) -> usize {
+                match self {
+                    $(Self::$variant(board) => board.size()),*
+                }
+            }
+
+            pub fn set(&mut self, row: usize, col: usize, value: usize) -> Result<bool, String> {
+                match self {
+                    $(Self::$variant(board) => board.set(row, col, value)),*
+                }
+            }
+
+            pub fn constrain_parity(
+                &mut self,
+                row: usize,
+                col: usize,
+                parity: Parity,
+            ) -> Result<bool, String> {
+                match self {
+                    $(Self::$variant(board) => board.constrain_parity(row, col, parity)),*
+                }
+            }
+
+            /// Keeps only the values of a pencil mark in the cell.
+            pub fn retain_values(
+                &mut self,
+                row: usize,
+                col: usize,
+                values: &[usize],
+            ) -> Result<bool, String> {
+                match self {
+                    $(Self::$variant(board) => board.retain_values(row, col, values)),*
+                }
+            }
+
+            pub fn is_solved(&self) -> bool {
+                match self {
+                    $(Self::$variant(board) => board.is_solved()),*
+                }
+            }
+
+            pub fn has_unique_solution(&self) -> bool {
+                match self {
+                    $(Self::$variant(board) => {
+                        DlxSolver::from_board(*board.clone()).has_unique_solution()
+                    }),*
+                }
+            }
+
+            pub fn to_variant(&self) -> VariantPuzzle {
+                match self {
+                    $(Self::$variant(board) => VariantPuzzle::from_board(board)),*
+                }
+            }
+
+            /// Solves the board with the exact cover backend.
+            pub fn solve_dlx(self) -> Result<Self, String> {
+                match self {
+                    $(Self::$variant(board) => {
+                        DlxSolver::from_board(*board).solve().map(|board| Self::$variant(Box::new(board)))
+                    }),*
+                }
+            }
+        }
+
+        impl AnySolver {
+            pub fn from_board(board: AnyBoard) -> Self {
+                match board {
+                    $(AnyBoard::$variant(board) => {
+                        Self::$variant(Box::new(SudokuSolver::from_board(*board)))
+                    }),*
+                }
+            }
+
+            pub fn with_cages(self, cages: Vec<Cage>) -> Self {
+                match self {
+                    $(Self::$variant(solver) => Self::$variant(Box::new(solver.with_cages(cages)))),*
+                }
+            }
+
+            pub fn with_adjacencies(self, adjacencies: Vec<Adjacency>) -> Self {
+                match self {
+                    $(Self::$variant(solver) => {
+                        Self::$variant(Box::new(solver.with_adjacencies(adjacencies)))
+                    }),*
+                }
+            }
+
+            pub fn with_lines(self, lines: Vec<Line>) -> Self {
+                match self {
+                    $(Self::$variant(solver) => Self::$variant(Box::new(solver.with_lines(lines)))),*
+                }
+            }
+
+            pub fn with_outside_clues(self, clues: Vec<OutsideClue>) -> Self {
+                match self {
+                    $(Self::$variant(solver) => {
+                        Self::$variant(Box::new(solver.with_outside_clues(clues)))
+                    }),*
+                }
+            }
+
+            pub fn with_search(self) -> Self {
+                match self {
+                    $(Self::$variant(solver) => Self::$variant(Box::new(solver.with_search()))),*
+                }
+            }
+
+            pub fn solve(self) -> Result<AnyBoard, String> {
+                match self {
+                    $(Self::$variant(solver) => {
+                        solver.solve().map(|board| AnyBoard::$variant(Box::new(board)))
+                    }),*
+                }
+            }
+        }
+
+        impl Debug for AnyBoard {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    $(Self::$variant(board) => write!(f, "{board:?}")),*
+                }
+            }
+        }
+
+        impl Display for AnyBoard {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    $(Self::$variant(board) => write!(f, "{board}")),*
+                }
+            }
+        }
+    };
+}
+
+any_size!(
+    4 => Size4,
+    6 => Size6,
+    8 => Size8,
+    9 => Size9,
+    10 => Size10,
+    12 => Size12,
+    16 => Size16,
+    25 => Size25,
+    36 => Size36,
+    49 => Size49,
+    64 => Size64,
+    81 => Size81,
+    100 => Size100,
+);
+
+impl AnyBoard {
+    /// Reads a puzzle in the `parse_puzzle` format, its size is the one of the puzzle and its
+    /// regions the ones of the layout, like a jigsaw or a Sudoku-X.
+    pub fn from_puzzle(text: &str, layout: RegionLayout) -> Result<Self, String> {
+        let (size, givens) = parse_puzzle(text)?;
+        if layout.size() != size {
+            return Err(format!(
+                "The layout is for size {} but the puzzle is of size {size}.",
+                layout.size()
+            ));
+        }
+        Self::with_layout(layout)?.with_givens(&givens)
+    }
+
+    fn with_givens(mut self, givens: &[Given]) -> Result<Self, String> {
+        for &(row, col, value) in givens {
+            self.set(row, col, value)?;
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnyBoard, AnySolver};
+    use crate::puzzle::parse_puzzle;
+    use crate::region::RegionLayout;
+
+    #[test]
+    fn detects_size_from_puzzle() {
+        let puzzle = "....43.......1.2";
+        let board = AnyBoard::from_puzzle(puzzle, RegionLayout::for_size(4)).unwrap();
+        assert_eq!(board.size(), 4);
+        assert!(board.has_unique_solution());
+
+        let solved = AnySolver::from_board(board.clone()).solve().unwrap();
+        let expected = board.solve_dlx().unwrap();
+        assert!(solved.is_solved());
+        assert_eq!(solved.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn rejects_unsupported_sizes() {
+        assert!(parse_puzzle("1234567").is_err());
+        let layout = RegionLayout::with_box_shape(1, 11);
+        assert!(AnyBoard::from_puzzle(&".".repeat(11 * 11), layout).is_err());
+    }
+}
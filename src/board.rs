@@ -1,9 +1,29 @@
 use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
 use crate::possibility_matrix::possibility_iterator::PossibilityIterator;
+#[cfg(test)]
+use crate::possibility_matrix::transform::Transform;
 use crate::possibility_matrix::PossibilityMatrix;
-use crate::region::RegionType;
+use crate::region::{default_box_shape, Region, RegionLayout, RegionType};
 use crate::subset::Subset;
 use std::fmt::{Debug, Display, Formatter};
+use std::rc::Rc;
+
+/// Whether a cell holds an odd or an even value, like the shaded cells of odd/even sudoku.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Parity {
+    Odd,
+    Even,
+}
+
+impl Parity {
+    pub fn values(self, size: usize) -> Vec<usize> {
+        let first = match self {
+            Parity::Odd => 1,
+            Parity::Even => 2,
+        };
+        (first..=size).step_by(2).collect()
+    }
+}
 
 pub enum ExcludedPos<'a> {
     Single(usize, usize),
@@ -25,6 +45,13 @@ impl ExcludedPos<'_> {
         }
     }
 
+    fn is_within(&self, region: &[(usize, usize)]) -> bool {
+        match self {
+            ExcludedPos::Single(row, col) => region.contains(&(*row, *col)),
+            ExcludedPos::Group(points) => points.iter().all(|point| region.contains(point)),
+        }
+    }
+
     fn is_excluded(&self, row: usize, col: usize) -> bool {
         match self {
             ExcludedPos::Single(e_row, e_col) => *e_col == col && *e_row == row,
@@ -35,11 +62,13 @@ impl ExcludedPos<'_> {
     }
 }
 
+#[derive(Clone)]
 pub struct SudokuBoard<const N: usize>
 where
     ForSize<N>: StorageForSize,
 {
     board: PossibilityMatrix<N>,
+    layout: Rc<RegionLayout>,
     pub improved: Vec<(usize, usize)>,
 }
 
@@ -48,24 +77,70 @@ where
     ForSize<N>: StorageForSize,
 {
     pub fn new() -> Self {
+        let (box_rows, box_cols) = default_box_shape(N);
+        Self::with_box_shape(box_rows, box_cols)
+    }
+
+    /// A board whose boxes are `box_rows` by `box_cols` cells, like 2x3 for 6x6 boards.
+    pub fn with_box_shape(box_rows: usize, box_cols: usize) -> Self {
+        Self::with_layout(RegionLayout::with_box_shape(box_rows, box_cols))
+    }
+
+    /// A board whose boxes are the ones of `layout`, like the irregular shapes of a jigsaw.
+    pub fn with_layout(layout: RegionLayout) -> Self {
+        assert!(
+            layout.size() == N,
+            "Invalid layout of size {} for size {N}",
+            layout.size()
+        );
+        let (box_rows, box_cols) = layout.box_shape().unwrap_or(default_box_shape(N));
         Self {
-            board: PossibilityMatrix::<N>::new(),
+            board: PossibilityMatrix::<N>::with_box_shape(box_rows, box_cols),
+            layout: Rc::new(layout),
             improved: Vec::new(),
         }
     }
 
+    pub fn from_givens(givens: &[(usize, usize, usize)]) -> Result<Self, String> {
+        let mut board = Self::new();
+        for &(row, col, value) in givens {
+            board.set(row, col, value)?;
+        }
+        Ok(board)
+    }
+
     pub const fn size(&self) -> usize {
         self.board.size()
     }
 
-    pub const fn block_size(&self) -> usize {
-        self.board.block_size()
+    pub const fn box_rows(&self) -> usize {
+        self.board.box_rows()
+    }
+
+    pub const fn box_cols(&self) -> usize {
+        self.board.box_cols()
+    }
+
+    pub fn layout(&self) -> &RegionLayout {
+        &self.layout
+    }
+
+    pub fn regions(&self) -> Rc<[Region]> {
+        self.layout.regions()
     }
 
     pub fn get_possible_values(&self, row: usize, col: usize) -> PossibilityIterator<N> {
         self.board.get_possible_values(row, col)
     }
 
+    pub fn get_value(&self, row: usize, col: usize) -> Option<usize> {
+        if self.board.is_cell_resolved(row, col) {
+            self.board.get_possible_values(row, col).next()
+        } else {
+            None
+        }
+    }
+
     pub fn is_solved(&self) -> bool {
         self.board.is_board_resolved()
     }
@@ -84,10 +159,54 @@ where
         self.remove_from_row(&ExcludedPos::Single(row, col), value)?;
         self.remove_from_col(&ExcludedPos::Single(row, col), value)?;
         self.remove_from_box(&ExcludedPos::Single(row, col), value)?;
+        self.remove_from_extra_regions(&ExcludedPos::Single(row, col), value)?;
+        self.remove_from_chess_peers(row, col, value)?;
         Ok(self.board.is_board_resolved())
     }
 
-    fn remove_value(&mut self, row: usize, col: usize, value: usize) -> Result<bool, String> {
+    /// Removes the candidates of the cell that aren't in `values`.
+    pub fn retain_values(
+        &mut self,
+        row: usize,
+        col: usize,
+        values: &[usize],
+    ) -> Result<bool, String> {
+        let removed: Vec<_> = self
+            .board
+            .get_possible_values(row, col)
+            .filter(|value| !values.contains(value))
+            .collect();
+        for value in removed {
+            let is_solved = self.remove_value(row, col, value)?;
+            if is_solved {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Keeps the odd or the even candidates of the cell.
+    pub fn constrain_parity(
+        &mut self,
+        row: usize,
+        col: usize,
+        parity: Parity,
+    ) -> Result<bool, String> {
+        self.retain_values(row, col, &parity.values(N))
+    }
+
+    /// Transforms the board, candidates included, to check the rules under symmetry.
+    #[cfg(test)]
+    pub fn transform(&mut self, transform: &Transform) -> Result<(), String> {
+        if !self.layout.is_classic() {
+            return Err(
+                "Can't transform a board with a jigsaw layout or variant rules.".to_string(),
+            );
+        }
+        self.board.transform(transform)
+    }
+
+    pub fn remove_value(&mut self, row: usize, col: usize, value: usize) -> Result<bool, String> {
         if self.board.is_cell_resolved(row, col) {
             if self.board.get_possible_values(row, col).next().unwrap() == value {
                 return Err(format!(
@@ -156,19 +275,38 @@ where
         excluded_point: &ExcludedPos,
         value: usize,
     ) -> Result<bool, String> {
-        let box_row =
-            (excluded_point.firsts_row() / self.board.block_size()) * self.board.block_size();
-        let box_col =
-            (excluded_point.firsts_col() / self.board.block_size()) * self.board.block_size();
-        for i in 0..self.board.block_size() {
-            for j in 0..self.board.block_size() {
-                let current_row = box_row + i;
-                let current_col = box_col + j;
-                if excluded_point.is_excluded(current_row, current_col) {
+        let layout = Rc::clone(&self.layout);
+        let box_ = layout.box_of(excluded_point.firsts_row(), excluded_point.firsts_col());
+        for &(row, col) in box_ {
+            if excluded_point.is_excluded(row, col) {
+                continue;
+            }
+
+            let is_solved = self.remove_value(row, col, value)?;
+            if is_solved {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Removes the value from every extra region containing all the excluded positions.
+    fn remove_from_extra_regions(
+        &mut self,
+        excluded_point: &ExcludedPos,
+        value: usize,
+    ) -> Result<bool, String> {
+        let layout = Rc::clone(&self.layout);
+        for (_, region) in layout.extra_regions() {
+            if !excluded_point.is_within(region) {
+                continue;
+            }
+            for &(row, col) in region {
+                if excluded_point.is_excluded(row, col) {
                     continue;
                 }
 
-                let is_solved = self.remove_value(current_row, current_col, value)?;
+                let is_solved = self.remove_value(row, col, value)?;
                 if is_solved {
                     return Ok(true);
                 }
@@ -177,6 +315,22 @@ where
         Ok(false)
     }
 
+    fn remove_from_chess_peers(
+        &mut self,
+        row: usize,
+        col: usize,
+        value: usize,
+    ) -> Result<bool, String> {
+        let layout = Rc::clone(&self.layout);
+        for &(peer_row, peer_col) in layout.chess_peers(row, col) {
+            let is_solved = self.remove_value(peer_row, peer_col, value)?;
+            if is_solved {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     pub fn apply_external_subset(
         &mut self,
         region_type: RegionType,
@@ -188,6 +342,12 @@ where
                 RegionType::Row => self.remove_from_row(&ExcludedPos::Group(positions), value)?,
                 RegionType::Col => self.remove_from_col(&ExcludedPos::Group(positions), value)?,
                 RegionType::Box => self.remove_from_box(&ExcludedPos::Group(positions), value)?,
+                RegionType::Diagonal
+                | RegionType::Extra
+                | RegionType::DisjointGroup
+                | RegionType::Window => {
+                    self.remove_from_extra_regions(&ExcludedPos::Group(positions), value)?
+                }
             };
             if is_solved {
                 return Ok(true);
@@ -241,12 +401,66 @@ where
     }
 }
 
+impl<const N: usize> SudokuBoard<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    /// Draws a jigsaw board, each cell `cell_width` wide, with lines only on the borders of its
+    /// boxes.
+    fn fmt_jigsaw(
+        &self,
+        f: &mut Formatter<'_>,
+        cell_width: usize,
+        cell: impl Fn(&[usize]) -> String,
+    ) -> std::fmt::Result {
+        let layout = &self.layout;
+        let border = |first: (usize, usize), second: (usize, usize)| {
+            second.0 == N
+                || second.1 == N
+                || layout.box_index(first.0, first.1) != layout.box_index(second.0, second.1)
+        };
+
+        writeln!(f, "+{}", format!("{}+", "-".repeat(cell_width)).repeat(N))?;
+        for row in 0..N {
+            write!(f, "|")?;
+            for col in 0..N {
+                let values: Vec<_> = self.get_possible_values(row, col).collect();
+                let separator = if border((row, col), (row, col + 1)) {
+                    '|'
+                } else {
+                    ' '
+                };
+                write!(f, "{:<cell_width$}{separator}", cell(&values))?;
+            }
+            writeln!(f)?;
+
+            write!(f, "+")?;
+            for col in 0..N {
+                let line = if border((row, col), (row + 1, col)) {
+                    "-"
+                } else {
+                    " "
+                };
+                write!(f, "{}+", line.repeat(cell_width))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
 impl<const N: usize> Debug for SudokuBoard<N>
 where
     ForSize<N>: StorageForSize,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        std::fmt::Debug::fmt(&self.board, f)
+        if self.layout.box_shape().is_some() {
+            return std::fmt::Debug::fmt(&self.board, f);
+        }
+        self.fmt_jigsaw(f, N * 2 + 1, |values| {
+            let values: Vec<_> = values.iter().map(ToString::to_string).collect();
+            format!(" {}", values.join(","))
+        })
     }
 }
 
@@ -255,6 +469,40 @@ where
     ForSize<N>: StorageForSize,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        std::fmt::Display::fmt(&self.board, f)
+        if self.layout.box_shape().is_some() {
+            return std::fmt::Display::fmt(&self.board, f);
+        }
+        self.fmt_jigsaw(f, 3, |values| match values {
+            [] => " ! ".to_string(),
+            [value] => format!(" {value} "),
+            _ => " _ ".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SudokuBoard;
+    use crate::puzzle::parse_layout;
+
+    #[test]
+    fn draws_jigsaw_boxes() {
+        let mut board = SudokuBoard::<4>::with_layout(parse_layout("1112 1322 3342 3444").unwrap());
+        board.set(0, 0, 1).unwrap();
+        assert_eq!(
+            board.to_string(),
+            "\
++---+---+---+---+
+| 1   _   _ | _ |
++   +---+---+   +
+| _ | _ | _   _ |
++---+   +---+   +
+| _   _ | _ | _ |
++   +---+   +---+
+| _ | _   _   _ |
++---+---+---+---+
+"
+        );
+        assert!(format!("{board:?}").starts_with("+---------+"));
     }
 }
@@ -0,0 +1,244 @@
+use crate::board::SudokuBoard;
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::puzzle::{to_puzzle_string, Given};
+use crate::region::default_box_shape;
+use itertools::Itertools;
+
+/// The largest size whose row and column orders can all be tried, 10x10 boards already have
+/// about 110 million pairs of them.
+pub const MAX_CANONICAL_SIZE: usize = 9;
+
+/// All the orders of `bands * band_size` lines that keep the lines of each band together.
+fn band_preserving_orders(bands: usize, band_size: usize) -> Vec<Vec<usize>> {
+    let within_band: Vec<Vec<usize>> = (0..band_size).permutations(band_size).collect();
+
+    let mut orders = Vec::new();
+    for band_order in (0..bands).permutations(bands) {
+        for line_orders in (0..bands)
+            .map(|_| within_band.iter())
+            .multi_cartesian_product()
+        {
+            orders.push(
+                band_order
+                    .iter()
+                    .zip(line_orders)
+                    .flat_map(|(&band, lines)| {
+                        lines.iter().map(move |&line| band * band_size + line)
+                    })
+                    .collect(),
+            );
+        }
+    }
+    orders
+}
+
+/// Relabels the digits of `grid` seen through `rows` and `cols` in order of appearance into
+/// `candidate`, giving up as soon as it can't be smaller than `best`.
+/// Returns whether `candidate` is now smaller than `best`.
+fn relabel_if_smaller(
+    grid: &[Vec<usize>],
+    rows: &[usize],
+    cols: &[usize],
+    best: Option<&[usize]>,
+    candidate: &mut Vec<usize>,
+) -> bool {
+    let mut labels = vec![0; grid.len() + 1];
+    let mut next_label = 1;
+    let mut is_smaller = best.is_none();
+    candidate.clear();
+
+    for &row in rows {
+        for &col in cols {
+            let value = grid[row][col];
+            let label = if value == 0 {
+                0
+            } else {
+                if labels[value] == 0 {
+                    labels[value] = next_label;
+                    next_label += 1;
+                }
+                labels[value]
+            };
+
+            if let (false, Some(best)) = (is_smaller, best) {
+                match label.cmp(&best[candidate.len()]) {
+                    std::cmp::Ordering::Less => is_smaller = true,
+                    std::cmp::Ordering::Greater => return false,
+                    std::cmp::Ordering::Equal => {}
+                }
+            }
+            candidate.push(label);
+        }
+    }
+    is_smaller
+}
+
+/// The lexicographically smallest string among all the grids equivalent to `grid` (0 marking
+/// the empty cells) by digit relabeling, permuting rows (columns) within bands (stacks),
+/// permuting bands (stacks) and transposition (only with square boxes).
+///
+/// The search goes over every row and column order, which is quick for 9x9 boards but grows
+/// too fast to be practical for the bigger ones, so they are rejected.
+fn canonical_grid(grid: &[Vec<usize>], box_rows: usize, box_cols: usize) -> Result<String, String> {
+    let size = grid.len();
+    if size > MAX_CANONICAL_SIZE {
+        return Err(format!(
+            "Canonical forms are only computed up to {MAX_CANONICAL_SIZE}x{MAX_CANONICAL_SIZE} boards, found {size}x{size}."
+        ));
+    }
+    let transposed: Vec<Vec<usize>> = (0..size)
+        .map(|col| grid.iter().map(|values| values[col]).collect())
+        .collect();
+    let grids = if box_rows == box_cols {
+        vec![grid, &transposed]
+    } else {
+        vec![grid]
+    };
+    let row_orders = band_preserving_orders(size / box_rows, box_rows);
+    let col_orders = band_preserving_orders(size / box_cols, box_cols);
+
+    let mut best: Option<Vec<usize>> = None;
+    let mut candidate = Vec::with_capacity(size * size);
+    for grid in grids {
+        for rows in &row_orders {
+            for cols in &col_orders {
+                if relabel_if_smaller(grid, rows, cols, best.as_deref(), &mut candidate) {
+                    best = Some(candidate.clone());
+                }
+            }
+        }
+    }
+
+    let givens: Vec<_> = best
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, value)| value != 0)
+        .map(|(i, value)| (i / size, i % size, value))
+        .collect();
+    Ok(to_puzzle_string(size, &givens))
+}
+
+/// Canonical form of the resolved cells of the board, only for classic layouts: the
+/// transformations don't keep jigsaw boxes or variant regions.
+pub fn canonical_form<const N: usize>(board: &SudokuBoard<N>) -> Result<String, String>
+where
+    ForSize<N>: StorageForSize,
+{
+    if !board.layout().is_classic() {
+        return Err(
+            "Can't compute the canonical form of a board with a jigsaw layout or variant rules."
+                .to_string(),
+        );
+    }
+    let grid: Vec<Vec<usize>> = (0..N)
+        .map(|row| {
+            (0..N)
+                .map(|col| board.get_value(row, col).unwrap_or(0))
+                .collect()
+        })
+        .collect();
+    canonical_grid(&grid, board.box_rows(), board.box_cols())
+}
+
+/// Canonical form of a puzzle, only its givens are taken into account (a board would also
+/// resolve whatever the givens imply, making puzzles with the same solution look alike).
+pub fn canonical_puzzle<const N: usize>(givens: &[Given]) -> Result<String, String>
+where
+    ForSize<N>: StorageForSize,
+{
+    let mut grid = vec![vec![0; N]; N];
+    for &(row, col, value) in givens {
+        grid[row][col] = value;
+    }
+    let (box_rows, box_cols) = default_box_shape(N);
+    canonical_grid(&grid, box_rows, box_cols)
+}
+
+pub fn are_equivalent<const N: usize>(
+    a: &SudokuBoard<N>,
+    b: &SudokuBoard<N>,
+) -> Result<bool, String>
+where
+    ForSize<N>: StorageForSize,
+{
+    Ok(canonical_form(a)? == canonical_form(b)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{are_equivalent, canonical_puzzle};
+    use crate::board::SudokuBoard;
+    use crate::puzzle::{parse_layout, parse_puzzle, Given};
+    use crate::region::RegionLayout;
+
+    const PUZZLE: &str =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    fn canonical(givens: &[Given]) -> String {
+        canonical_puzzle::<9>(givens).unwrap()
+    }
+
+    #[test]
+    fn equivalent_puzzles_have_the_same_form() {
+        let (_, givens) = parse_puzzle(PUZZLE).unwrap();
+        let expected = canonical(&givens);
+
+        let relabeled: Vec<_> = givens
+            .iter()
+            .map(|&(row, col, value)| (row, col, value % 9 + 1))
+            .collect();
+        // rows 0 and 2 of the first band, and the first and last bands.
+        let swap_rows = |row| match row {
+            0 => 2,
+            2 => 0,
+            _ => row,
+        };
+        let row_swapped: Vec<_> = givens
+            .iter()
+            .map(|&(row, col, value)| (swap_rows(row), col, value))
+            .collect();
+        let band_swapped: Vec<_> = givens
+            .iter()
+            .map(|&(row, col, value)| ((row + 6) % 9 / 3 * 3 + row % 3, col, value))
+            .collect();
+        let transposed: Vec<_> = givens
+            .iter()
+            .map(|&(row, col, value)| (col, row, value))
+            .collect();
+        for other in [relabeled, row_swapped, band_swapped, transposed] {
+            assert_ne!(other, givens);
+            assert_eq!(canonical(&other), expected);
+        }
+    }
+
+    #[test]
+    fn different_puzzles_have_different_forms() {
+        let (_, givens) = parse_puzzle(PUZZLE).unwrap();
+        let mut other = givens.clone();
+        other.pop();
+        assert_ne!(canonical(&other), canonical(&givens));
+        other.push((8, 8, 1));
+        assert_ne!(canonical(&other), canonical(&givens));
+    }
+
+    #[test]
+    fn rejects_large_sizes() {
+        assert!(canonical_puzzle::<16>(&[(0, 0, 1)]).is_err());
+        assert_eq!(
+            canonical_puzzle::<4>(&[(3, 3, 4)]),
+            Ok("...............1".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_jigsaw_and_variant_boards() {
+        let classic = SudokuBoard::<4>::new();
+        assert_eq!(are_equivalent(&classic, &classic), Ok(true));
+
+        let jigsaw = SudokuBoard::<4>::with_layout(parse_layout("1112 1322 3342 3444").unwrap());
+        assert!(are_equivalent(&jigsaw, &jigsaw).is_err());
+        let diagonal = SudokuBoard::<4>::with_layout(RegionLayout::for_size(4).with_diagonals());
+        assert!(are_equivalent(&classic, &diagonal).is_err());
+    }
+}
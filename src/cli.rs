@@ -0,0 +1,394 @@
+use crate::any_board::{AnyBoard, AnySolver};
+use crate::board::SudokuBoard;
+use crate::canonical::{are_equivalent, canonical_puzzle};
+use crate::dlx::dlx_solver::DlxSolver;
+use crate::fpuzzles::import_fpuzzles;
+use crate::generator::{DifficultyTarget, PuzzleGenerator, Symmetry};
+use crate::minimizer::minimize;
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::possibility_matrix::transform::{transform_givens, Transform};
+use crate::puzzle::{
+    parse_cages, parse_dots, parse_extra_regions, parse_gattai, parse_inequalities, parse_layout,
+    parse_lines, parse_outside_clues, parse_parity, parse_puzzle, to_puzzle_string, Given,
+};
+use crate::region::{ChessConstraint, RegionLayout};
+use crate::solver::adjacency::non_consecutive;
+use crate::solver::gattai::{GattaiLayout, GattaiSolver};
+use crate::solver::rating::{rate, DifficultyLevel, Technique};
+use crate::variant::VariantPuzzle;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Calls `$function::<N>(...)` with the const `N` matching the runtime `$size`,
+/// returning an error from the caller for unsupported sizes.
+macro_rules! with_size {
+    ($size:expr, $function:ident($($arg:expr),*)) => {
+        match $size {
+            4 => $function::<4>($($arg),*),
+            6 => $function::<6>($($arg),*),
+            8 => $function::<8>($($arg),*),
+            9 => $function::<9>($($arg),*),
+            10 => $function::<10>($($arg),*),
+            12 => $function::<12>($($arg),*),
+            16 => $function::<16>($($arg),*),
+            25 => $function::<25>($($arg),*),
+            36 => $function::<36>($($arg),*),
+            49 => $function::<49>($($arg),*),
+            64 => $function::<64>($($arg),*),
+            81 => $function::<81>($($arg),*),
+            100 => $function::<100>($($arg),*),
+            size => return Err(format!("Unsupported board size {size}.")),
+        }
+    };
+}
+
+/// Returns the value following `--name` in the arguments.
+pub fn get_option<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg.strip_prefix("--") == Some(name))
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+pub fn parse_option<T: std::str::FromStr>(
+    args: &[String],
+    name: &str,
+) -> Result<Option<T>, String> {
+    get_option(args, name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("Invalid value '{value}' for --{name}."))
+        })
+        .transpose()
+}
+
+fn generate<const N: usize>(
+    seed: u64,
+    symmetry: Symmetry,
+    target: Option<(DifficultyTarget, usize, Duration)>,
+) -> Result<(), String>
+where
+    ForSize<N>: StorageForSize,
+{
+    let mut generator = PuzzleGenerator::<N>::new(seed).with_symmetry(symmetry);
+    let puzzle = match target {
+        Some((target, max_attempts, time_limit)) => {
+            let (puzzle, difficulty) =
+                generator.generate_with_difficulty(&target, max_attempts, time_limit)?;
+            println!("{difficulty}");
+            puzzle
+        }
+        None => generator.generate(),
+    };
+    println!("seed: {seed}, givens: {}", puzzle.givens.len());
+    println!("{}", to_puzzle_string(N, &puzzle.givens));
+    println!("Solution:\n{}", puzzle.solution);
+    Ok(())
+}
+
+/// `generate [--size N] [--seed SEED] [--symmetry none|rotational|diagonal|mirror]
+/// [--difficulty easy|medium|hard|expert | --technique TECHNIQUE] [--attempts N] [--time SECONDS]`
+pub fn generate_command(args: &[String]) -> Result<(), String> {
+    let size = parse_option(args, "size")?.unwrap_or(9);
+    let seed = parse_option(args, "seed")?.unwrap_or_else(rand::random);
+    let symmetry = get_option(args, "symmetry").map_or(Ok(Symmetry::None), str::parse)?;
+
+    let target = match (
+        parse_option::<DifficultyLevel>(args, "difficulty")?,
+        parse_option::<Technique>(args, "technique")?,
+    ) {
+        (Some(_), Some(_)) => return Err("Use either --difficulty or --technique.".to_string()),
+        (Some(level), None) => Some(DifficultyTarget::from(level)),
+        (None, Some(technique)) => Some(DifficultyTarget::Technique(technique)),
+        (None, None) => None,
+    };
+    let target = match target {
+        Some(target) => {
+            let max_attempts = parse_option(args, "attempts")?.unwrap_or(1000);
+            let time_limit = Duration::from_secs(parse_option(args, "time")?.unwrap_or(60));
+            Some((target, max_attempts, time_limit))
+        }
+        None => None,
+    };
+
+    with_size!(size, generate(seed, symmetry, target))
+}
+
+/// Reads the text of a puzzle given either inline or as a path to a file.
+fn read_puzzle_text(arg: &str) -> Result<String, String> {
+    if std::path::Path::new(arg).is_file() {
+        std::fs::read_to_string(arg).map_err(|e| format!("Can't read {arg}: {e}"))
+    } else {
+        Ok(arg.to_string())
+    }
+}
+
+/// Reads a puzzle given either inline or as a path to a file.
+fn read_puzzle(arg: &str) -> Result<(usize, Vec<Given>), String> {
+    parse_puzzle(&read_puzzle_text(arg)?)
+}
+
+/// `solve <PUZZLE|FILE> [--layout LAYOUT|FILE] [--diagonal] [--windoku] [--disjoint-groups]
+/// [--extra-regions REGIONS|FILE] [--anti-knight] [--anti-king] [--parity PARITY|FILE]
+/// [--cages CAGES|FILE] [--dots DOTS|FILE] [--non-consecutive] [--inequalities SIGNS|FILE]
+/// [--lines LINES|FILE] [--clues CLUES|FILE] [--search] [--dlx]`, the size of the board is the
+/// one of the puzzle, a layout turns it into a jigsaw, the next options add extra regions, chess
+/// constraints, odd and even cells (`parse_parity`), killer cages (`parse_cages`), Kropki dots
+/// (`parse_dots`), the non-consecutive rule, greater-than signs (`parse_inequalities`), lines
+/// (`parse_lines`) and outside clues (`parse_outside_clues`), and `--search` guesses when the
+/// rules are stuck.
+pub fn solve_command(args: &[String]) -> Result<(), String> {
+    let puzzle = read_puzzle_text(args.first().ok_or("Missing puzzle to solve.")?)?;
+    let mut layout = match get_option(args, "layout") {
+        Some(layout) => parse_layout(&read_puzzle_text(layout)?)?,
+        None => RegionLayout::for_size(parse_puzzle(&puzzle)?.0),
+    };
+    if args.iter().any(|arg| arg == "--diagonal") {
+        layout = layout.with_diagonals();
+    }
+    if args.iter().any(|arg| arg == "--windoku") {
+        layout = layout.with_windows()?;
+    }
+    if args.iter().any(|arg| arg == "--disjoint-groups") {
+        layout = layout.with_disjoint_groups()?;
+    }
+    if let Some(regions) = get_option(args, "extra-regions") {
+        layout = parse_extra_regions(&read_puzzle_text(regions)?, layout)?;
+    }
+    if args.iter().any(|arg| arg == "--anti-knight") {
+        layout = layout.with_chess_constraint(ChessConstraint::AntiKnight);
+    }
+    if args.iter().any(|arg| arg == "--anti-king") {
+        layout = layout.with_chess_constraint(ChessConstraint::AntiKing);
+    }
+    let mut board = AnyBoard::from_puzzle(&puzzle, layout)?;
+    println!("Solving a {0}x{0} board.", board.size());
+    if let Some(parity) = get_option(args, "parity") {
+        for (row, col, parity) in parse_parity(&read_puzzle_text(parity)?, board.size())? {
+            board.constrain_parity(row, col, parity)?;
+        }
+    }
+
+    let cages = get_option(args, "cages")
+        .map(|cages| parse_cages(&read_puzzle_text(cages)?, board.size()))
+        .transpose()?;
+
+    let mut adjacencies = get_option(args, "dots")
+        .map(|dots| parse_dots(&read_puzzle_text(dots)?, board.size()))
+        .transpose()?
+        .unwrap_or_default();
+    if args.iter().any(|arg| arg == "--non-consecutive") {
+        adjacencies.extend(non_consecutive(board.size()));
+    }
+    if let Some(signs) = get_option(args, "inequalities") {
+        adjacencies.extend(parse_inequalities(&read_puzzle_text(signs)?, board.size())?);
+    }
+
+    let lines = get_option(args, "lines")
+        .map(|lines| parse_lines(&read_puzzle_text(lines)?, board.size()))
+        .transpose()?;
+
+    let clues = get_option(args, "clues")
+        .map(|clues| parse_outside_clues(&read_puzzle_text(clues)?, board.size()))
+        .transpose()?;
+
+    let solved_board = if args.iter().any(|arg| arg == "--dlx") {
+        if cages.is_some() || !adjacencies.is_empty() || lines.is_some() || clues.is_some() {
+            return Err(
+                "The exact cover backend doesn't support cages, dots, signs, lines or clues."
+                    .to_string(),
+            );
+        }
+        if !board.has_unique_solution() {
+            println!("This board does not have a unique solution.");
+        }
+        board.solve_dlx()?
+    } else {
+        let mut solver = AnySolver::from_board(board);
+        // cages, dots, signs, lines and clues often need guesses the rules alone can't make.
+        let mut search = args.iter().any(|arg| arg == "--search");
+        if let Some(cages) = cages {
+            solver = solver.with_cages(cages);
+            search = true;
+        }
+        if !adjacencies.is_empty() {
+            solver = solver.with_adjacencies(adjacencies);
+            search = true;
+        }
+        if let Some(lines) = lines {
+            solver = solver.with_lines(lines);
+            search = true;
+        }
+        if let Some(clues) = clues {
+            solver = solver.with_outside_clues(clues);
+            search = true;
+        }
+        if search {
+            solver = solver.with_search();
+        }
+        solver.solve()?
+    };
+
+    println!("Final Board:\n{solved_board:?}");
+    if solved_board.is_solved() {
+        println!("Solved:\n{solved_board}");
+    } else {
+        println!("Could not solve the board.");
+    }
+    Ok(())
+}
+
+fn solve_samurai<const N: usize>(text: &str, search: bool) -> Result<(), String>
+where
+    ForSize<N>: StorageForSize,
+{
+    let layout = GattaiLayout::samurai(N)?;
+    let mut solver = GattaiSolver::<N>::new(layout.clone());
+    for (row, col, value) in parse_gattai(text, &layout)? {
+        solver.set(row, col, value)?;
+    }
+    if search {
+        solver = solver.with_search();
+    }
+
+    let solved_board = solver.solve()?;
+    if solved_board.is_solved() {
+        println!("Solved:\n{solved_board}");
+    } else {
+        println!("Could not solve the board:\n{solved_board}");
+    }
+    Ok(())
+}
+
+/// `variant <JSON|FILE>`, solves a puzzle in the `VariantPuzzle` format and writes the solved
+/// board back in it, along with the rules of the puzzle.
+pub fn variant_command(args: &[String]) -> Result<(), String> {
+    let text = read_puzzle_text(args.first().ok_or("Missing puzzle to solve.")?)?;
+    let puzzle = VariantPuzzle::from_json(&text)?;
+    let solved_board = puzzle.solver()?.solve()?;
+    if solved_board.is_solved() {
+        println!("Solved:\n{solved_board}");
+    } else {
+        println!("Could not solve the board.");
+    }
+
+    println!("{}", puzzle.with_board(&solved_board).to_json());
+    Ok(())
+}
+
+/// `import <LINK|FILE>`, reads a puzzle made with f-puzzles from its link, or the compressed
+/// puzzle in it, and writes it in the `VariantPuzzle` format for the `variant` command.
+pub fn import_command(args: &[String]) -> Result<(), String> {
+    let text = read_puzzle_text(args.first().ok_or("Missing puzzle to import.")?)?;
+    println!("{}", import_fpuzzles(&text)?.to_json());
+    Ok(())
+}
+
+/// `samurai <PUZZLE|FILE> [--search]`, the puzzle of each of the five grids on its own line
+/// (`parse_gattai`), from the top left one to the bottom right one with the middle one third.
+pub fn samurai_command(args: &[String]) -> Result<(), String> {
+    let text = read_puzzle_text(args.first().ok_or("Missing puzzle to solve.")?)?;
+    let first = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .ok_or("Missing puzzle to solve.")?;
+    let size = parse_puzzle(first)?.0;
+    let search = args.iter().any(|arg| arg == "--search");
+    with_size!(size, solve_samurai(&text, search))
+}
+
+/// `rate <PUZZLE|FILE>`
+pub fn rate_command(args: &[String]) -> Result<(), String> {
+    let puzzle = args.first().ok_or("Missing puzzle to rate.")?;
+    let (size, givens) = read_puzzle(puzzle)?;
+
+    let difficulty = with_size!(size, rate(&givens))?;
+    println!("{difficulty}");
+    Ok(())
+}
+
+/// `minimize <PUZZLE|FILE> [--symmetry none|rotational|diagonal|mirror]`
+pub fn minimize_command(args: &[String]) -> Result<(), String> {
+    let puzzle = args.first().ok_or("Missing puzzle to minimize.")?;
+    let (size, givens) = read_puzzle(puzzle)?;
+    let symmetry = get_option(args, "symmetry").map_or(Ok(Symmetry::None), str::parse)?;
+
+    let minimal = with_size!(size, minimize(&givens, symmetry))?;
+    println!(
+        "removed {} of {} givens",
+        givens.len() - minimal.len(),
+        givens.len()
+    );
+    println!("{}", to_puzzle_string(size, &minimal));
+    Ok(())
+}
+
+/// `canonical <PUZZLE|FILE> [--dedup]`, files hold one puzzle per line of at most
+/// `MAX_CANONICAL_SIZE`.
+pub fn canonical_command(args: &[String]) -> Result<(), String> {
+    let input = args.first().ok_or("Missing puzzles to canonicalize.")?;
+    let dedup = args.iter().any(|arg| arg == "--dedup");
+    let text = read_puzzle_text(input)?;
+
+    let mut seen = HashSet::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let (size, givens) = parse_puzzle(line)?;
+        let canonical = with_size!(size, canonical_puzzle(&givens))?;
+        if !dedup {
+            println!("{canonical}");
+        } else if seen.insert(canonical) {
+            println!("{}", line.trim());
+        }
+    }
+    Ok(())
+}
+
+/// Whether the puzzles are equivalent and whether their solutions are.
+fn equivalent<const N: usize>(a: &[Given], b: &[Given]) -> Result<(bool, bool), String>
+where
+    ForSize<N>: StorageForSize,
+{
+    let solution_a = DlxSolver::from_board(SudokuBoard::<N>::from_givens(a)?).solve()?;
+    let solution_b = DlxSolver::from_board(SudokuBoard::<N>::from_givens(b)?).solve()?;
+    Ok((
+        canonical_puzzle::<N>(a)? == canonical_puzzle::<N>(b)?,
+        are_equivalent(&solution_a, &solution_b)?,
+    ))
+}
+
+/// `equivalent <PUZZLE|FILE> <PUZZLE|FILE>`, for puzzles of at most `MAX_CANONICAL_SIZE`.
+pub fn equivalent_command(args: &[String]) -> Result<(), String> {
+    let [a, b, ..] = args else {
+        return Err("Expected two puzzles to compare.".to_string());
+    };
+    let (size, a) = read_puzzle(a)?;
+    let (other_size, b) = read_puzzle(b)?;
+    if size != other_size {
+        println!("not equivalent");
+        return Ok(());
+    }
+
+    let (puzzles, solutions) = with_size!(size, equivalent(&a, &b))?;
+    match (puzzles, solutions) {
+        (true, _) => println!("equivalent"),
+        (false, true) => println!("not equivalent, but their solutions are"),
+        (false, false) => println!("not equivalent"),
+    }
+    Ok(())
+}
+
+/// `transform <PUZZLE|FILE> <TRANSFORM>...`, see `Transform::from_str` for the transforms.
+pub fn transform_command(args: &[String]) -> Result<(), String> {
+    let puzzle = args.first().ok_or("Missing puzzle to transform.")?;
+    let (size, givens) = read_puzzle(puzzle)?;
+    let transforms = args[1..]
+        .iter()
+        .map(|arg| arg.parse())
+        .collect::<Result<Vec<Transform>, _>>()?;
+
+    let transformed = with_size!(size, transform_givens(&givens, &transforms))?;
+    println!("{}", to_puzzle_string(size, &transformed));
+    Ok(())
+}
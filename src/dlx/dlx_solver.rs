@@ -0,0 +1,233 @@
+use crate::board::SudokuBoard;
+use crate::dlx::DancingLinks;
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+/// Exact cover backend: every cell holds one value and every region holds every value once.
+pub struct DlxSolver<const N: usize>
+where
+    ForSize<N>: StorageForSize,
+{
+    board: SudokuBoard<N>,
+    pre_solve_error: Option<String>,
+}
+
+impl<const N: usize> DlxSolver<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    pub fn new() -> Self {
+        Self::from_board(SudokuBoard::<N>::new())
+    }
+
+    pub const fn from_board(board: SudokuBoard<N>) -> Self {
+        Self {
+            board,
+            pre_solve_error: None,
+        }
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: usize) {
+        if self.pre_solve_error.is_some() {
+            return;
+        }
+        if let Err(msg) = self.board.set(row, col, value) {
+            self.pre_solve_error = Some(msg);
+        }
+    }
+
+    /// Builds the exact cover matrix, one row per remaining candidate of the board.
+    ///
+    /// Chess constraints add a secondary column per pair of peers and value: each can be
+    /// covered at most once.
+    ///
+    /// Shuffling the candidates randomizes which solution is found first.
+    fn build_links(&self, rng: Option<&mut StdRng>) -> (DancingLinks, Vec<(usize, usize, usize)>) {
+        let size = self.board.size();
+        let regions = self.board.regions();
+        let mut region_index = vec![vec![Vec::new(); size]; size];
+        for (i, (_, region)) in regions.iter().enumerate() {
+            for &(row, col) in region {
+                region_index[row][col].push(i);
+            }
+        }
+
+        let mut candidates: Vec<_> = (0..size)
+            .flat_map(|row| (0..size).map(move |col| (row, col)))
+            .flat_map(|(row, col)| {
+                self.board
+                    .get_possible_values(row, col)
+                    .map(move |value| (row, col, value))
+            })
+            .collect();
+        if let Some(rng) = rng {
+            candidates.shuffle(rng);
+        }
+
+        let mut peer_index = vec![vec![Vec::new(); size]; size];
+        let mut peer_pairs = 0;
+        for row in 0..size {
+            for col in 0..size {
+                for &(peer_row, peer_col) in self.board.layout().chess_peers(row, col) {
+                    if (row, col) < (peer_row, peer_col) {
+                        peer_index[row][col].push(peer_pairs);
+                        peer_index[peer_row][peer_col].push(peer_pairs);
+                        peer_pairs += 1;
+                    }
+                }
+            }
+        }
+
+        let primary = size * size + regions.len() * size;
+        let mut links = DancingLinks::new(primary, peer_pairs * size);
+        for &(row, col, value) in &candidates {
+            let mut columns = vec![row * size + col];
+            columns.extend(
+                region_index[row][col]
+                    .iter()
+                    .map(|region| size * size + region * size + value - 1),
+            );
+            columns.extend(
+                peer_index[row][col]
+                    .iter()
+                    .map(|pair| primary + pair * size + value - 1),
+            );
+            links.add_row(&columns);
+        }
+        (links, candidates)
+    }
+
+    fn to_board(&self, solution: &[(usize, usize, usize)]) -> Result<SudokuBoard<N>, String> {
+        let mut board = self.board.clone();
+        for &(row, col, value) in solution {
+            if board.get_value(row, col).is_none() {
+                board.set(row, col, value)?;
+            }
+        }
+        Ok(board)
+    }
+
+    /// Returns up to `limit` solutions of the board.
+    pub fn solutions(&self, limit: usize) -> Result<Vec<SudokuBoard<N>>, String> {
+        if let Some(error_msg) = &self.pre_solve_error {
+            return Err(error_msg.clone());
+        }
+        let (mut links, candidates) = self.build_links(None);
+        let mut solutions = Vec::new();
+        links.search(&mut |rows| {
+            solutions.push(rows.iter().map(|&r| candidates[r]).collect::<Vec<_>>());
+            solutions.len() < limit
+        });
+        solutions
+            .iter()
+            .map(|solution| self.to_board(solution))
+            .collect()
+    }
+
+    /// Counts the solutions of the board, stopping once `limit` were found.
+    pub fn count_solutions(&self, limit: usize) -> Result<usize, String> {
+        if let Some(error_msg) = &self.pre_solve_error {
+            return Err(error_msg.clone());
+        }
+        let (mut links, _) = self.build_links(None);
+        Ok(links.count_solutions(limit))
+    }
+
+    pub fn has_unique_solution(&self) -> bool {
+        self.count_solutions(2).is_ok_and(|count| count == 1)
+    }
+
+    /// Returns a random solution of the board, reproducible for a given `rng` state.
+    pub fn solve_shuffled(self, rng: &mut StdRng) -> Result<SudokuBoard<N>, String> {
+        if let Some(error_msg) = &self.pre_solve_error {
+            return Err(error_msg.clone());
+        }
+        let (mut links, candidates) = self.build_links(Some(rng));
+        let mut solution = Vec::new();
+        links.search(&mut |rows| {
+            solution.extend(rows.iter().map(|&r| candidates[r]));
+            false
+        });
+        if solution.is_empty() {
+            return Err("This board has no solution.".to_string());
+        }
+        self.to_board(&solution)
+    }
+
+    pub fn solve(self) -> Result<SudokuBoard<N>, String> {
+        self.solutions(1)?
+            .pop()
+            .ok_or_else(|| "This board has no solution.".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DlxSolver;
+    use crate::solver::SudokuSolver;
+    use crate::{KNOWN_VALUES, KNOWN_VALUES2, KNOWN_VALUES3};
+
+    fn dlx_solver(known_values: &[(usize, usize, usize)]) -> DlxSolver<9> {
+        let mut solver = DlxSolver::<9>::new();
+        for &(row, col, value) in known_values {
+            solver.set(row, col, value);
+        }
+        solver
+    }
+
+    #[test]
+    fn matches_logical_solver() {
+        for known_values in [&KNOWN_VALUES[..], &KNOWN_VALUES2[..], &KNOWN_VALUES3[..]] {
+            let mut logical_solver = SudokuSolver::<9>::new();
+            for &(row, col, value) in known_values {
+                logical_solver.set(row, col, value);
+            }
+            let logical_board = logical_solver.solve().unwrap();
+            let dlx_board = dlx_solver(known_values).solve().unwrap();
+
+            assert!(dlx_board.is_solved());
+            for row in 0..9 {
+                for col in 0..9 {
+                    if let Some(value) = logical_board.get_value(row, col) {
+                        assert_eq!(dlx_board.get_value(row, col), Some(value));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn known_puzzles_are_unique() {
+        for known_values in [&KNOWN_VALUES[..], &KNOWN_VALUES2[..], &KNOWN_VALUES3[..]] {
+            assert!(dlx_solver(known_values).has_unique_solution());
+        }
+    }
+
+    #[test]
+    fn enumerates_multiple_solutions() {
+        let solver = dlx_solver(&KNOWN_VALUES3[1..]);
+        let solutions = solver.solutions(10).unwrap();
+        assert_eq!(solver.count_solutions(10).unwrap(), solutions.len());
+        assert!(solutions.len() > 1);
+        assert!(solutions.iter().all(|board| board.is_solved()));
+    }
+
+    #[test]
+    fn empty_board_has_many_solutions() {
+        assert_eq!(DlxSolver::<9>::new().count_solutions(5).unwrap(), 5);
+        assert!(DlxSolver::<9>::new().solve().unwrap().is_solved());
+    }
+
+    #[test]
+    fn contradiction_has_no_solution() {
+        let mut solver = dlx_solver(&KNOWN_VALUES2);
+        solver.set(0, 0, 1);
+        solver.set(1, 1, 2);
+        solver.set(2, 2, 3);
+        assert_eq!(
+            solver.count_solutions(1),
+            Err("Invalid Board, at (4,2) removed resolved value 6.".to_string())
+        );
+    }
+}
@@ -0,0 +1,209 @@
+pub mod dlx_solver;
+
+const ROOT: usize = 0;
+
+/// Knuth's Algorithm X over a toroidal doubly linked matrix.
+///
+/// Primary columns must be covered exactly once, secondary columns at most once.
+/// Node `0` is the root, nodes `1..=columns` are the column headers and the rest
+/// are the `1`s of the rows added with `add_row`.
+pub struct DancingLinks {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    row: Vec<usize>,
+    size: Vec<usize>,
+    columns: usize,
+    rows: usize,
+}
+
+impl DancingLinks {
+    pub fn new(primary_columns: usize, secondary_columns: usize) -> Self {
+        let columns = primary_columns + secondary_columns;
+        let mut links = Self {
+            left: Vec::with_capacity(columns + 1),
+            right: Vec::with_capacity(columns + 1),
+            up: Vec::with_capacity(columns + 1),
+            down: Vec::with_capacity(columns + 1),
+            column: Vec::with_capacity(columns + 1),
+            row: Vec::with_capacity(columns + 1),
+            size: vec![0; columns + 1],
+            columns,
+            rows: 0,
+        };
+
+        for header in 0..=columns {
+            // only the primary columns are reachable from the root.
+            let (left, right) = if header <= primary_columns {
+                (
+                    if header == ROOT {
+                        primary_columns
+                    } else {
+                        header - 1
+                    },
+                    if header == primary_columns {
+                        ROOT
+                    } else {
+                        header + 1
+                    },
+                )
+            } else {
+                (header, header)
+            };
+            links.left.push(left);
+            links.right.push(right);
+            links.up.push(header);
+            links.down.push(header);
+            links.column.push(header);
+            links.row.push(usize::MAX);
+        }
+        links
+    }
+
+    /// Adds a row covering the given (0 based) columns and returns its id.
+    pub fn add_row(&mut self, columns: &[usize]) -> usize {
+        let row_id = self.rows;
+        self.rows += 1;
+
+        let mut first = None;
+        for &col in columns {
+            assert!(col < self.columns, "Invalid column {col}");
+            let header = col + 1;
+            let node = self.column.len();
+
+            self.column.push(header);
+            self.row.push(row_id);
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            let last = self.up[header];
+            self.down[last] = node;
+            self.up[header] = node;
+            self.size[header] += 1;
+
+            match first {
+                None => {
+                    self.left.push(node);
+                    self.right.push(node);
+                    first = Some(node);
+                }
+                Some(first) => {
+                    let last = self.left[first];
+                    self.left.push(last);
+                    self.right.push(first);
+                    self.right[last] = node;
+                    self.left[first] = node;
+                }
+            }
+        }
+        row_id
+    }
+
+    fn cover(&mut self, header: usize) {
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = right;
+        self.left[right] = left;
+
+        let mut i = self.down[header];
+        while i != header {
+            let mut j = self.right[i];
+            while j != i {
+                let (up, down) = (self.up[j], self.down[j]);
+                self.down[up] = down;
+                self.up[down] = up;
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, header: usize) {
+        let mut i = self.up[header];
+        while i != header {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                let (up, down) = (self.up[j], self.down[j]);
+                self.down[up] = j;
+                self.up[down] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+
+        let (left, right) = (self.left[header], self.right[header]);
+        self.right[left] = header;
+        self.left[right] = header;
+    }
+
+    /// Picks the primary column with the fewest remaining rows.
+    fn choose_column(&self) -> usize {
+        let mut best = self.right[ROOT];
+        let mut header = self.right[best];
+        while header != ROOT && self.size[best] > 0 {
+            if self.size[header] < self.size[best] {
+                best = header;
+            }
+            header = self.right[header];
+        }
+        best
+    }
+
+    /// Calls `on_solution` with the row ids of every exact cover found, until it returns `false`.
+    pub fn search(&mut self, on_solution: &mut dyn FnMut(&[usize]) -> bool) {
+        let mut partial = Vec::new();
+        self.search_from(&mut partial, on_solution);
+    }
+
+    /// Returns `true` once the search should stop.
+    fn search_from(
+        &mut self,
+        partial: &mut Vec<usize>,
+        on_solution: &mut dyn FnMut(&[usize]) -> bool,
+    ) -> bool {
+        if self.right[ROOT] == ROOT {
+            return !on_solution(partial);
+        }
+
+        let header = self.choose_column();
+        if self.size[header] == 0 {
+            return false;
+        }
+
+        self.cover(header);
+        let mut stop = false;
+        let mut r = self.down[header];
+        while r != header && !stop {
+            partial.push(self.row[r]);
+            let mut j = self.right[r];
+            while j != r {
+                self.cover(self.column[j]);
+                j = self.right[j];
+            }
+
+            stop = self.search_from(partial, on_solution);
+
+            let mut j = self.left[r];
+            while j != r {
+                self.uncover(self.column[j]);
+                j = self.left[j];
+            }
+            partial.pop();
+            r = self.down[r];
+        }
+        self.uncover(header);
+        stop
+    }
+
+    /// Counts exact covers, stopping early once `limit` were found.
+    pub fn count_solutions(&mut self, limit: usize) -> usize {
+        let mut count = 0;
+        self.search(&mut |_| {
+            count += 1;
+            count < limit
+        });
+        count
+    }
+}
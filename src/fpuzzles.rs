@@ -0,0 +1,545 @@
+use crate::puzzle::{to_cell_string, to_layout_string, to_puzzle_string};
+use crate::region::{default_box_shape, RegionLayout};
+use crate::variant::{
+    Boxes, CageDefinition, DotDefinition, DotKind, LineDefinition, ParityDefinition, ParityKind,
+    PencilMark, Rule, VariantPuzzle,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=";
+
+/// The fields of an f-puzzles puzzle that don't change its solution.
+const METADATA: [&str; 4] = ["title", "author", "ruleset", "solution"];
+
+/// Reads bits from the 6 bit values of a base64 string, the lowest power first.
+struct BitReader {
+    values: Vec<usize>,
+    index: usize,
+    position: usize,
+}
+
+impl BitReader {
+    fn read(&mut self, count: u32) -> usize {
+        let mut bits = 0;
+        for power in 0..count {
+            let value = self.values.get(self.index).copied().unwrap_or(0);
+            if value & self.position != 0 {
+                bits |= 1 << power;
+            }
+            self.position >>= 1;
+            if self.position == 0 {
+                self.position = 32;
+                self.index += 1;
+            }
+        }
+        bits
+    }
+}
+
+/// Decompresses the output of `compressToBase64` from the LZ-string library, which f-puzzles
+/// uses for its links.
+fn decompress_from_base64(input: &str) -> Result<String, String> {
+    let values = input
+        .bytes()
+        .map(|byte| {
+            BASE64
+                .iter()
+                .position(|&symbol| symbol == byte)
+                .ok_or_else(|| format!("Invalid base64 character '{}'.", byte as char))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let length = values.len();
+    let mut reader = BitReader {
+        values,
+        index: 0,
+        position: 32,
+    };
+
+    // the first 3 codes mark an 8 bit character, a 16 bit one and the end.
+    let mut dictionary: Vec<Vec<u16>> = vec![Vec::new(); 3];
+    let mut enlarge_in = 4;
+    let mut num_bits = 3;
+    let first = match reader.read(2) {
+        0 => reader.read(8),
+        1 => reader.read(16),
+        _ => return Ok(String::new()),
+    };
+    let mut word = vec![first as u16];
+    dictionary.push(word.clone());
+    let mut result = word.clone();
+
+    loop {
+        if reader.index >= length {
+            return Err("The compressed puzzle is truncated.".to_string());
+        }
+        let mut code = reader.read(num_bits);
+        match code {
+            0 | 1 => {
+                let character = reader.read(if code == 0 { 8 } else { 16 });
+                dictionary.push(vec![character as u16]);
+                code = dictionary.len() - 1;
+                enlarge_in -= 1;
+            }
+            2 => {
+                return String::from_utf16(&result)
+                    .map_err(|_| "The compressed puzzle is not valid text.".to_string());
+            }
+            _ => {}
+        }
+        if enlarge_in == 0 {
+            enlarge_in = 1 << num_bits;
+            num_bits += 1;
+        }
+
+        let entry = match dictionary.get(code) {
+            Some(entry) => entry.clone(),
+            None if code == dictionary.len() => [word.as_slice(), &word[..1]].concat(),
+            None => return Err(format!("Invalid code {code} in the compressed puzzle.")),
+        };
+        result.extend(&entry);
+        dictionary.push([word.as_slice(), &entry[..1]].concat());
+        enlarge_in -= 1;
+        word = entry;
+        if enlarge_in == 0 {
+            enlarge_in = 1 << num_bits;
+            num_bits += 1;
+        }
+    }
+}
+
+/// The compressed puzzle of an f-puzzles (`?load=...`) or SudokuPad (`/fpuzzles...`) link, or
+/// the text itself when it isn't one.
+fn payload(text: &str) -> String {
+    let text = text.trim();
+    let text = text
+        .split_once("load=")
+        .map_or(text, |(_, rest)| rest.split('&').next().unwrap_or(rest));
+    let text = text.rsplit_once("fpuzzles").map_or(text, |(_, rest)| rest);
+    text.replace("%2B", "+")
+        .replace("%2F", "/")
+        .replace("%3D", "=")
+        .replace(' ', "+")
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GridCell {
+    value: Option<usize>,
+    #[serde(default)]
+    given: bool,
+    /// The box of the cell, counting from 0 in reading order, when it isn't the default one.
+    region: Option<usize>,
+    #[serde(default)]
+    given_pencil_marks: Vec<usize>,
+}
+
+/// Any constraint drawn over the grid: the cells of a cage or a dot, the circle of an arrow,
+/// the lines of a line constraint, the cell of an odd or even cell or of an outside clue, the
+/// direction of a little killer clue and its clue.
+#[derive(Deserialize)]
+struct Shape {
+    #[serde(default)]
+    cells: Vec<String>,
+    #[serde(default)]
+    lines: Vec<Vec<String>>,
+    cell: Option<String>,
+    direction: Option<String>,
+    value: Option<Value>,
+}
+
+impl Shape {
+    /// The clue, written as a number or a string.
+    fn value(&self) -> Option<String> {
+        match &self.value {
+            Some(Value::Number(number)) => Some(number.to_string()),
+            Some(Value::String(text)) if !text.is_empty() => Some(text.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// The row and column of a cell like `R0C3`, counting from 1, 0 and `size + 1` being outside of
+/// the grid.
+fn outside_cell(text: &str, size: usize) -> Option<(usize, usize)> {
+    let lowercase = text.to_ascii_lowercase();
+    let (row, col) = lowercase.strip_prefix('r')?.split_once('c')?;
+    let (row, col) = (row.parse().ok()?, col.parse().ok()?);
+    (row <= size + 1 && col <= size + 1).then_some((row, col))
+}
+
+/// A sandwich or little killer clue in the `parse_outside_clues` format.
+fn outside_clue(name: &str, shape: &Shape, size: usize) -> Option<String> {
+    let sum = shape.value()?;
+    let (row, col) = outside_cell(shape.cell.as_deref()?, size)?;
+    let inside = |index: usize| (1..=size).contains(&index);
+    let edge = |index: usize| index == 0 || index == size + 1;
+    if name == "sandwichsum" {
+        return if inside(row) && edge(col) {
+            Some(format!("sandwich r{row} {sum}"))
+        } else if edge(row) && inside(col) {
+            Some(format!("sandwich c{col} {sum}"))
+        } else {
+            None
+        };
+    }
+    let (direction, step) = match shape.direction.as_deref()? {
+        "UR" => ("ne", (-1, 1)),
+        "UL" => ("nw", (-1, -1)),
+        "DR" => ("se", (1, 1)),
+        "DL" => ("sw", (1, -1)),
+        _ => return None,
+    };
+    let start = (
+        row.checked_add_signed(step.0)?,
+        col.checked_add_signed(step.1)?,
+    );
+    (inside(start.0) && inside(start.1))
+        .then(|| format!("little-killer r{}c{} {direction} {sum}", start.0, start.1))
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::Array(values) => values.is_empty(),
+        Value::Object(fields) => fields.is_empty(),
+        Value::String(text) => text.is_empty(),
+        _ => false,
+    }
+}
+
+/// Reads a puzzle made with f-puzzles, from its compressed JSON or a link sharing it with
+/// f-puzzles or SudokuPad.
+///
+/// The givens, boxes, given pencil marks, extra regions, killer cages, lines, Kropki dots, XV
+/// pairs, odd and even cells, and sandwich and little killer clues are imported, along with the
+/// diagonal, chess, disjoint groups and non-consecutive rules.
+/// Any other constraint fails the import, naming it, rather than solving a different puzzle.
+pub fn import_fpuzzles(text: &str) -> Result<VariantPuzzle, String> {
+    let json = decompress_from_base64(&payload(text))?;
+    let mut fields: serde_json::Map<String, Value> =
+        serde_json::from_str(&json).map_err(|err| format!("Invalid f-puzzles puzzle: {err}."))?;
+    let size = fields
+        .remove("size")
+        .and_then(|size| size.as_u64())
+        .ok_or("An f-puzzles puzzle must have a size.")? as usize;
+    let grid: Vec<Vec<GridCell>> = fields
+        .remove("grid")
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|err| format!("Invalid f-puzzles grid: {err}."))?
+        .unwrap_or_default();
+    if grid.len() != size || grid.iter().any(|row| row.len() != size) {
+        return Err(format!(
+            "The grid of an f-puzzles puzzle of size {size} must be {size}x{size}."
+        ));
+    }
+
+    let mut puzzle = read_grid(&grid, size)?;
+    let mut diagonals = Vec::new();
+    let mut unsupported = Vec::new();
+    for (name, value) in fields {
+        if is_empty(&value) || METADATA.contains(&name.as_str()) {
+            continue;
+        }
+        let shapes = || {
+            serde_json::from_value::<Vec<Shape>>(value.clone())
+                .map_err(|err| format!("Invalid f-puzzles {name}: {err}."))
+        };
+        match name.as_str() {
+            "diagonal+" => diagonals.push((0..size).map(|i| (size - 1 - i, i)).collect()),
+            "diagonal-" => diagonals.push((0..size).map(|i| (i, i)).collect()),
+            "antiknight" => puzzle.rules.push(Rule::AntiKnight),
+            "antiking" => puzzle.rules.push(Rule::AntiKing),
+            "disjointgroups" => puzzle.rules.push(Rule::DisjointGroups),
+            "nonconsecutive" => puzzle.rules.push(Rule::NonConsecutive),
+            "negative" => match value.as_array().map(Vec::as_slice) {
+                Some([first, second])
+                    if [first, second].contains(&&Value::from("ratio"))
+                        && [first, second].contains(&&Value::from("difference")) =>
+                {
+                    puzzle.rules.push(Rule::NegativeDots);
+                }
+                _ => unsupported.push(format!("negative {value}")),
+            },
+            "extraregion" => {
+                puzzle
+                    .extra_regions
+                    .extend(shapes()?.into_iter().map(|shape| shape.cells));
+            }
+            "killercage" => {
+                for shape in shapes()? {
+                    match shape.value().map(|sum| sum.parse()) {
+                        Some(Ok(sum)) => puzzle.cages.push(CageDefinition {
+                            sum,
+                            cells: shape.cells,
+                        }),
+                        _ => unsupported.push(format!("{name} without a sum")),
+                    }
+                }
+            }
+            "odd" | "even" => {
+                let kind = match name.as_str() {
+                    "odd" => ParityKind::Odd,
+                    _ => ParityKind::Even,
+                };
+                for shape in shapes()? {
+                    match shape.cell {
+                        Some(cell) => puzzle.parity.push(ParityDefinition { kind, cell }),
+                        None => unsupported.push(format!("{name} without a cell")),
+                    }
+                }
+            }
+            "sandwichsum" | "littlekillersum" => {
+                for shape in shapes()? {
+                    match outside_clue(&name, &shape, size) {
+                        Some(clue) => puzzle.clues.push(clue),
+                        None => unsupported.push(format!("{name} at {:?}", shape.cell)),
+                    }
+                }
+            }
+            "thermometer" | "palindrome" | "renban" | "whispers" => {
+                let kind = match name.as_str() {
+                    "thermometer" => "thermo",
+                    "whispers" => "whisper",
+                    kind => kind,
+                };
+                for shape in shapes()? {
+                    let gap = size.div_ceil(2).to_string();
+                    if kind == "whisper" && shape.value().is_some_and(|value| value != gap) {
+                        unsupported.push(format!("whispers of difference {:?}", shape.value));
+                        continue;
+                    }
+                    puzzle
+                        .lines
+                        .extend(shape.lines.into_iter().map(|cells| LineDefinition {
+                            kind: kind.to_string(),
+                            cells,
+                        }));
+                }
+            }
+            "arrow" => {
+                for shape in shapes()? {
+                    let ([circle], [line]) = (shape.cells.as_slice(), shape.lines.as_slice())
+                    else {
+                        unsupported.push("arrow with a pill or several lines".to_string());
+                        continue;
+                    };
+                    let mut cells = line.clone();
+                    if cells.first() != Some(circle) {
+                        cells.insert(0, circle.clone());
+                    }
+                    puzzle.lines.push(LineDefinition {
+                        kind: "arrow".to_string(),
+                        cells,
+                    });
+                }
+            }
+            "difference" | "ratio" => {
+                let (kind, default) = match name.as_str() {
+                    "difference" => (DotKind::White, "1"),
+                    _ => (DotKind::Black, "2"),
+                };
+                for shape in shapes()? {
+                    let value = shape.value();
+                    match <[String; 2]>::try_from(shape.cells) {
+                        Ok(cells) if value.as_deref().is_none_or(|value| value == default) => {
+                            puzzle.dots.push(DotDefinition { kind, cells });
+                        }
+                        _ => unsupported.push(format!("{name} of {value:?}")),
+                    }
+                }
+            }
+            "xv" => {
+                for shape in shapes()? {
+                    match shape.value().as_deref() {
+                        Some(kind @ ("X" | "V")) => puzzle.lines.push(LineDefinition {
+                            kind: kind.to_ascii_lowercase(),
+                            cells: shape.cells,
+                        }),
+                        value => unsupported.push(format!("xv of {value:?}")),
+                    }
+                }
+            }
+            _ => unsupported.push(name),
+        }
+    }
+
+    if diagonals.len() == 2 {
+        puzzle.rules.push(Rule::Diagonal);
+    } else {
+        puzzle.extra_regions.extend(
+            diagonals
+                .into_iter()
+                .map(|diagonal: Vec<_>| diagonal.into_iter().map(to_cell_string).collect()),
+        );
+    }
+    if !unsupported.is_empty() {
+        return Err(format!(
+            "Unsupported f-puzzles constraints: {}.",
+            unsupported.join(", ")
+        ));
+    }
+    // checks the cells and the constraints.
+    puzzle.solver()?;
+    Ok(puzzle)
+}
+
+/// The givens, boxes and given pencil marks of the grid.
+fn read_grid(grid: &[Vec<GridCell>], size: usize) -> Result<VariantPuzzle, String> {
+    let (box_rows, box_cols) = default_box_shape(size);
+    let default_box = |row: usize, col: usize| row / box_rows * (size / box_cols) + col / box_cols;
+    let cells = || (0..size).flat_map(|row| (0..size).map(move |col| (row, col)));
+
+    let boxes = if cells().all(|(row, col)| {
+        grid[row][col]
+            .region
+            .is_none_or(|region| region == default_box(row, col))
+    }) {
+        None
+    } else {
+        let labels: Vec<Vec<usize>> = (0..size)
+            .map(|row| {
+                (0..size)
+                    .map(|col| grid[row][col].region.unwrap_or(default_box(row, col)))
+                    .collect()
+            })
+            .collect();
+        let layout = RegionLayout::from_grid(&labels)?;
+        Some(Boxes::Jigsaw(
+            to_layout_string(&layout)
+                .lines()
+                .map(str::to_string)
+                .collect(),
+        ))
+    };
+
+    let mut givens = Vec::new();
+    for (row, col) in cells() {
+        match grid[row][col].value.filter(|_| grid[row][col].given) {
+            Some(value) if value == 0 || value > size => {
+                return Err(format!(
+                    "Invalid value {value} at ({row},{col}) expected between 1 and {size}."
+                ));
+            }
+            Some(value) => givens.push((row, col, value)),
+            None => {}
+        }
+    }
+    let pencil_marks = cells()
+        .filter(|&(row, col)| !grid[row][col].given_pencil_marks.is_empty())
+        .map(|(row, col)| PencilMark {
+            cell: to_cell_string((row, col)),
+            values: grid[row][col].given_pencil_marks.clone(),
+        })
+        .collect();
+
+    Ok(VariantPuzzle {
+        size,
+        boxes,
+        givens: to_puzzle_string(size, &givens),
+        pencil_marks,
+        ..VariantPuzzle::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decompress_from_base64, import_fpuzzles};
+    use crate::variant::{Boxes, Rule};
+
+    /// A 4x4 killer with a thermometer, a V, Kropki dots and its solution.
+    const KILLER: &str = "N4IgzglgXgpiBcAWANCALhNAbO8QBUYw0RUBDAVzQAsB7AJwRAGUY00ZHV6Kcw2mAOQYBbMlgAEPPqXC0sVCLQB2CANoBGZACZkAZmQoDKLbt1ajh/TuQaAuqgDm9CABN1a0ADdxFXFpBHCC8YVXg0HhgAX2RgGLjYqIdPeJAfBVxEeOzkhLyc5E9A4NCABVCAYwgsAFkyegBrMHVEO2zExLsHEAbqnHoKskdcIoqYLCxm+DUQACVtAGE9WXmFxBBu9L8mZaTUGk4RWhE2TnVQLAhlIg85vQXtFfuNDbbugA8vc5AxiamZ1brVCze7rTa+XAgABqID2IFcEAAZojOJURqBfpN1HMNEsVriwXD6GQMLRvpj/nc8cDEHi3lEgA";
+
+    /// A 4x4 jigsaw with one diagonal and most lines.
+    const JIGSAW: &str = "N4IgzglgXgpiBcAWANCA5gJwgEwQbT1AxjQgHsA7BABgF9kiTyr46GRjTKb7GuWAjKgBuAQwA2AVzjwATKlLCYLAC4ZptALrJCHJt1a89/BLKOdmCAef2CtOvpbk2Tz9hYMBmF0+vbdHixm7rYI3iGu4Y5eWtog2BCiaJQSANQIatKoohQqEADWEBRoCABmEmAwqAlgAFZkRSqYZJIADmBlFVUgFJQAxpSVfZJ5Shnq3RQkoqMyeBwz5CDVEKWlMMQUfXBxmwBGOfig4kUwHfAEIABKAgDCAsvXsreyIJqa9iAA7gAWEGCtDbnXQnKbA+ZXRD3R5XTwvN4fOKtCRFbAYMgAWzmx1O4OuUNeqEht08MKhiARn1EGHRXyOIG24nEwOudwpcVBZ3wELZMOepPenxgAA81NTQhdQIzmfhWfCic8KUS4Q8iVCBZ8+kk5h8gA==";
+
+    /// A 4x4 with even and odd cells, sandwiches on the first row and column and a little killer.
+    const CLUES: &str = "N4IgzglgXgpiBcAWANCA5gJwgEwQbT2AF9ljSTiBdZQiu86285qms9uy6kGANxgB2+UAGMYAG3EIQAJQCMAYQBMICiDGTpMpctXcA9tlzxC6iVPizECxHtRgAhgOwB3CCIAWYAK4BbYWaalvIKAAwgqLwO4t5wlgCsqqSBFrKhCnIRIFExcSCJRNziEAAuJeIwANYQkjAYPv4mouZa6ZmoGuJgADIQYCX4sooqqNoKAMxZMuM2INzYEBgwIiUQ+kKWACIyWTmx0gBsekRAA==";
+
+    /// A minimum cell, a sandwich inside the grid, a killer cage without a sum, a cosmetic cage
+    /// and the negative XV rule.
+    const UNSUPPORTED: &str = "N4IgzglgXgpiBcAWANCA5gJwgEwQbT2AF9ljSTiBdZQiu86285qms9uy6kAWwgDsIPAK498oAMYwANtIQgASgEYAwkpBFuYAIb9sAdwgSAFmFHiQU2fIUAmFbZCoAbtunC48EAGYN3ANYQsjAYEtponoSWMtJg+Iqq6qjKDiCUmqhhERZWsfEK3iq+yYgqiGkubh7yAKx+qPwwaNoALhDOkSAAHs5pREA====";
+
+    #[test]
+    fn decompresses_lz_strings() {
+        assert_eq!(
+            decompress_from_base64("BYS4NmD2AEDuBvAnMATANNUEYOS6gyORGbhRA===="),
+            Ok("héllo wörld, héllo wörld ✓✓✓ héllo".to_string())
+        );
+        assert!(decompress_from_base64("N4Ig!").is_err());
+        assert!(decompress_from_base64(&KILLER[..40]).is_err());
+    }
+
+    #[test]
+    fn imports_killer_puzzles() {
+        let link = format!("https://www.f-puzzles.com/?load={KILLER}");
+        let puzzle = import_fpuzzles(&link).unwrap();
+        assert_eq!(
+            puzzle,
+            import_fpuzzles(&format!("https://sudokupad.app/fpuzzles{KILLER}")).unwrap()
+        );
+        // only given values are kept.
+        assert_eq!(puzzle.givens, "1...............");
+        assert_eq!(puzzle.pencil_marks[0].values, [4]);
+        assert_eq!(puzzle.cages[0].sum, 3);
+        assert_eq!(puzzle.dots.len(), 2);
+
+        let solved = puzzle.solver().unwrap().solve().unwrap();
+        assert_eq!(solved.to_variant().givens, "1234341221434321");
+    }
+
+    #[test]
+    fn imports_jigsaw_puzzles() {
+        let puzzle = import_fpuzzles(JIGSAW).unwrap();
+        assert_eq!(
+            puzzle.boxes,
+            Some(Boxes::Jigsaw(vec![
+                "1112".to_string(),
+                "1322".to_string(),
+                "3342".to_string(),
+                "3444".to_string(),
+            ]))
+        );
+        assert_eq!(puzzle.givens, "...2............");
+        assert_eq!(puzzle.rules, [Rule::NegativeDots, Rule::NonConsecutive]);
+        // a single diagonal is an extra region.
+        assert_eq!(puzzle.extra_regions.len(), 2);
+        assert_eq!(puzzle.extra_regions[1], ["r4c1", "r3c2", "r2c3", "r1c4"]);
+        let kinds: Vec<_> = puzzle.lines.iter().map(|line| line.kind.as_str()).collect();
+        assert_eq!(kinds, ["arrow", "palindrome", "renban", "whisper"]);
+        assert_eq!(puzzle.lines[0].cells, ["R1C4", "R2C3"]);
+    }
+
+    #[test]
+    fn imports_parity_and_outside_clues() {
+        let puzzle = import_fpuzzles(CLUES).unwrap();
+        assert_eq!(puzzle.parity.len(), 3);
+        assert_eq!(
+            puzzle.clues,
+            ["little-killer r1c2 se 6", "sandwich r1 5", "sandwich c1 5"]
+        );
+        let solved = puzzle.solver().unwrap().solve().unwrap();
+        assert_eq!(solved.to_variant().givens, "1234341221434321");
+    }
+
+    #[test]
+    fn reports_unsupported_constraints() {
+        let err = import_fpuzzles(UNSUPPORTED).unwrap_err();
+        assert!(
+            err.starts_with("Unsupported f-puzzles constraints:"),
+            "{err}"
+        );
+        for name in [
+            "killercage without a sum",
+            "negative",
+            "minimum",
+            "sandwichsum",
+        ] {
+            assert!(err.contains(name), "{err}");
+        }
+        // cages without a sum are only drawn, unlike killer cages.
+        let names = err["Unsupported f-puzzles constraints: ".len()..].trim_end_matches('.');
+        assert!(names.split(", ").any(|name| name == "cage"), "{err}");
+    }
+}
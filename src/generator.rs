@@ -0,0 +1,284 @@
+use crate::board::SudokuBoard;
+use crate::dlx::dlx_solver::DlxSolver;
+use crate::minimizer::remove_redundant_givens;
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::solver::rating::{rate, Difficulty, DifficultyLevel, Technique};
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::SeedableRng;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Pattern the givens of a generated puzzle keep.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Symmetry {
+    None,
+    /// 180° rotation around the center.
+    Rotational,
+    /// Reflection over the main diagonal.
+    Diagonal,
+    /// Reflection over the vertical axis.
+    Mirror,
+}
+
+impl Symmetry {
+    /// All the positions that must be kept or removed together with `(row, col)`.
+    pub fn orbit(self, size: usize, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let mirrored = match self {
+            Symmetry::None => (row, col),
+            Symmetry::Rotational => (size - 1 - row, size - 1 - col),
+            Symmetry::Diagonal => (col, row),
+            Symmetry::Mirror => (row, size - 1 - col),
+        };
+        if mirrored == (row, col) {
+            vec![(row, col)]
+        } else {
+            vec![(row, col), mirrored]
+        }
+    }
+}
+
+impl FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Symmetry::None),
+            "rotational" => Ok(Symmetry::Rotational),
+            "diagonal" => Ok(Symmetry::Diagonal),
+            "mirror" => Ok(Symmetry::Mirror),
+            _ => Err(format!(
+                "Unknown symmetry '{s}', expected one of none, rotational, diagonal, mirror."
+            )),
+        }
+    }
+}
+
+/// What a puzzle generated by `generate_with_difficulty` must need.
+#[derive(Clone, Debug)]
+pub enum DifficultyTarget {
+    /// A score within the range.
+    Score(RangeInclusive<f32>),
+    /// Solvable, with this as the hardest technique needed.
+    Technique(Technique),
+}
+
+impl DifficultyTarget {
+    fn matches(&self, difficulty: &Difficulty) -> bool {
+        match self {
+            DifficultyTarget::Score(range) => range.contains(&difficulty.score),
+            DifficultyTarget::Technique(technique) => {
+                difficulty.solved && difficulty.hardest == Some(*technique)
+            }
+        }
+    }
+
+    fn is_too_hard(&self, difficulty: &Difficulty) -> bool {
+        match self {
+            DifficultyTarget::Score(range) => difficulty.score > *range.end(),
+            DifficultyTarget::Technique(technique) => {
+                !difficulty.solved || difficulty.hardest > Some(*technique)
+            }
+        }
+    }
+}
+
+impl From<DifficultyLevel> for DifficultyTarget {
+    fn from(level: DifficultyLevel) -> Self {
+        DifficultyTarget::Score(level.score_range())
+    }
+}
+
+pub struct GeneratedPuzzle<const N: usize>
+where
+    ForSize<N>: StorageForSize,
+{
+    pub givens: Vec<(usize, usize, usize)>,
+    pub solution: SudokuBoard<N>,
+}
+
+pub struct PuzzleGenerator<const N: usize> {
+    rng: StdRng,
+    symmetry: Symmetry,
+}
+
+impl<const N: usize> PuzzleGenerator<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            symmetry: Symmetry::None,
+        }
+    }
+
+    pub const fn with_symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    /// Returns a random completely filled valid grid.
+    pub fn fill_grid(&mut self) -> SudokuBoard<N> {
+        DlxSolver::<N>::new()
+            .solve_shuffled(&mut self.rng)
+            .expect("An empty board always has a solution.")
+    }
+
+    /// Removes givens in a random order, keeping each removal only if the solution stays unique.
+    pub fn remove_givens(
+        &mut self,
+        givens: &[(usize, usize, usize)],
+    ) -> Vec<(usize, usize, usize)> {
+        let mut positions: Vec<_> = givens.iter().map(|&(row, col, _)| (row, col)).collect();
+        positions.shuffle(&mut self.rng);
+        remove_redundant_givens::<N>(givens, &positions, self.symmetry)
+    }
+
+    /// Adds back a random missing given (with its symmetric ones) from the solution,
+    /// returns `false` if the puzzle is already complete.
+    fn add_given(&mut self, puzzle: &mut GeneratedPuzzle<N>) -> bool {
+        let missing: Vec<_> = (0..N)
+            .flat_map(|row| (0..N).map(move |col| (row, col)))
+            .filter(|&(row, col)| !puzzle.givens.iter().any(|&(r, c, _)| (r, c) == (row, col)))
+            .collect();
+        let Some(&(row, col)) = missing.choose(&mut self.rng) else {
+            return false;
+        };
+
+        for (r, c) in self.symmetry.orbit(N, row, col) {
+            if !puzzle.givens.iter().any(|&(gr, gc, _)| (gr, gc) == (r, c)) {
+                let value = puzzle.solution.get_value(r, c).unwrap();
+                puzzle.givens.push((r, c, value));
+            }
+        }
+        true
+    }
+
+    /// Generates puzzles until one rates within `target`, making a too hard puzzle easier
+    /// by adding givens back before giving up on it.
+    pub fn generate_with_difficulty(
+        &mut self,
+        target: &DifficultyTarget,
+        max_attempts: usize,
+        time_limit: Duration,
+    ) -> Result<(GeneratedPuzzle<N>, Difficulty), String> {
+        let start = Instant::now();
+        for _ in 0..max_attempts {
+            if start.elapsed() > time_limit {
+                break;
+            }
+
+            let mut puzzle = self.generate();
+            let mut difficulty = rate::<N>(&puzzle.givens)?;
+            while target.is_too_hard(&difficulty) && self.add_given(&mut puzzle) {
+                difficulty = rate::<N>(&puzzle.givens)?;
+            }
+            if target.matches(&difficulty) {
+                puzzle.givens.sort_unstable();
+                return Ok((puzzle, difficulty));
+            }
+        }
+        Err(format!(
+            "No puzzle matching {target:?} found within {max_attempts} attempts and {time_limit:?}."
+        ))
+    }
+
+    pub fn generate(&mut self) -> GeneratedPuzzle<N> {
+        let solution = self.fill_grid();
+        let all_values: Vec<_> = (0..N)
+            .flat_map(|row| (0..N).map(move |col| (row, col)))
+            .filter_map(|(row, col)| solution.get_value(row, col).map(|v| (row, col, v)))
+            .collect();
+        let givens = self.remove_givens(&all_values);
+        GeneratedPuzzle { givens, solution }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DifficultyTarget, PuzzleGenerator, Symmetry};
+    use crate::board::SudokuBoard;
+    use crate::dlx::dlx_solver::DlxSolver;
+    use crate::solver::rating::{rate, DifficultyLevel, Technique};
+    use std::time::Duration;
+
+    #[test]
+    fn same_seed_gives_the_same_puzzle() {
+        let first = PuzzleGenerator::<9>::new(42).generate();
+        let second = PuzzleGenerator::<9>::new(42).generate();
+        assert_eq!(first.givens, second.givens);
+        assert_eq!(first.solution.to_string(), second.solution.to_string());
+        let other = PuzzleGenerator::<9>::new(43).generate();
+        assert_ne!(first.solution.to_string(), other.solution.to_string());
+    }
+
+    #[test]
+    fn keeps_the_symmetry_of_the_givens() {
+        for symmetry in [
+            Symmetry::None,
+            Symmetry::Rotational,
+            Symmetry::Diagonal,
+            Symmetry::Mirror,
+        ] {
+            let puzzle = PuzzleGenerator::<9>::new(5)
+                .with_symmetry(symmetry)
+                .generate();
+            for &(row, col, _) in &puzzle.givens {
+                for (r, c) in symmetry.orbit(9, row, col) {
+                    assert!(
+                        puzzle.givens.iter().any(|&(gr, gc, _)| (gr, gc) == (r, c)),
+                        "{symmetry:?}: ({row},{col}) is given but not ({r},{c})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn puzzles_have_the_returned_solution_only() {
+        let puzzle = PuzzleGenerator::<9>::new(11).generate();
+        let solutions =
+            DlxSolver::from_board(SudokuBoard::<9>::from_givens(&puzzle.givens).unwrap())
+                .solutions(2)
+                .unwrap();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].to_string(), puzzle.solution.to_string());
+        assert!(puzzle.solution.is_solved());
+    }
+
+    #[test]
+    fn generates_puzzles_of_the_requested_difficulty() {
+        for level in [
+            DifficultyLevel::Easy,
+            DifficultyLevel::Medium,
+            DifficultyLevel::Hard,
+        ] {
+            let (puzzle, difficulty) = PuzzleGenerator::<9>::new(7)
+                .generate_with_difficulty(&level.into(), 200, Duration::from_secs(60))
+                .unwrap();
+            let score = rate::<9>(&puzzle.givens).unwrap().score;
+            assert_eq!(score, difficulty.score);
+            assert!(level.score_range().contains(&score), "{level:?} {score}");
+        }
+    }
+
+    #[test]
+    fn generates_puzzles_needing_a_technique() {
+        let target = DifficultyTarget::Technique(Technique::XWing);
+        let (puzzle, difficulty) = PuzzleGenerator::<9>::new(7)
+            .generate_with_difficulty(&target, 200, Duration::from_secs(60))
+            .unwrap();
+        assert!(difficulty.solved);
+        assert_eq!(difficulty.hardest, Some(Technique::XWing));
+        assert_eq!(
+            rate::<9>(&puzzle.givens).unwrap().hardest,
+            Some(Technique::XWing)
+        );
+
+        assert!(PuzzleGenerator::<9>::new(7)
+            .generate_with_difficulty(&target, 0, Duration::from_secs(60))
+            .is_err());
+    }
+}
@@ -1,11 +1,21 @@
+use crate::dlx::dlx_solver::DlxSolver;
 use crate::solver::SudokuSolver;
 
+mod any_board;
 mod board;
+mod canonical;
+mod cli;
+mod dlx;
+mod fpuzzles;
+mod generator;
 mod join;
+mod minimizer;
 mod possibility_matrix;
+mod puzzle;
 mod region;
 mod solver;
 mod subset;
+mod variant;
 
 #[allow(dead_code)]
 const KNOWN_VALUES: [(usize, usize, usize); 30] = [
@@ -90,20 +100,45 @@ const KNOWN_VALUES3: [(usize, usize, usize); 17] = [
     (8, 8, 9),
 ];
 
-fn main() {
-    let mut sudoku_solver = SudokuSolver::<9>::new();
-    for known in KNOWN_VALUES2 {
-        sudoku_solver.set(known.0, known.1, known.2);
-    }
-
-    let solved_board = sudoku_solver.solve();
-    match solved_board {
-        Ok(solved_board) => {
-            println!("Final Board:\n{solved_board:?}",);
-            println!("Solved:\n{solved_board:}",);
+fn solve_known_values(use_dlx: bool) -> Result<(), String> {
+    let solved_board = if use_dlx {
+        let mut dlx_solver = DlxSolver::<9>::new();
+        for known in KNOWN_VALUES2 {
+            dlx_solver.set(known.0, known.1, known.2);
+        }
+        if !dlx_solver.has_unique_solution() {
+            println!("This board does not have a unique solution.");
         }
-        Err(msg) => {
-            println!("{msg}");
+        dlx_solver.solve()?
+    } else {
+        let mut sudoku_solver = SudokuSolver::<9>::new();
+        for known in KNOWN_VALUES2 {
+            sudoku_solver.set(known.0, known.1, known.2);
         }
+        sudoku_solver.solve()?
+    };
+
+    println!("Final Board:\n{solved_board:?}",);
+    println!("Solved:\n{solved_board:}",);
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("solve") => cli::solve_command(&args[1..]),
+        Some("generate") => cli::generate_command(&args[1..]),
+        Some("rate") => cli::rate_command(&args[1..]),
+        Some("minimize") => cli::minimize_command(&args[1..]),
+        Some("canonical") => cli::canonical_command(&args[1..]),
+        Some("equivalent") => cli::equivalent_command(&args[1..]),
+        Some("transform") => cli::transform_command(&args[1..]),
+        Some("samurai") => cli::samurai_command(&args[1..]),
+        Some("variant") => cli::variant_command(&args[1..]),
+        Some("import") => cli::import_command(&args[1..]),
+        _ => solve_known_values(args.iter().any(|arg| arg == "--dlx")),
+    };
+    if let Err(msg) = result {
+        println!("{msg}");
     }
 }
@@ -0,0 +1,118 @@
+use crate::board::SudokuBoard;
+use crate::dlx::dlx_solver::DlxSolver;
+use crate::generator::Symmetry;
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::puzzle::Given;
+
+pub fn has_unique_solution<const N: usize>(givens: &[Given]) -> bool
+where
+    ForSize<N>: StorageForSize,
+{
+    SudokuBoard::<N>::from_givens(givens)
+        .is_ok_and(|board| DlxSolver::from_board(board).has_unique_solution())
+}
+
+fn grid_to_givens(grid: &[Vec<Option<usize>>]) -> Vec<Given> {
+    grid.iter()
+        .enumerate()
+        .flat_map(|(row, values)| {
+            values
+                .iter()
+                .enumerate()
+                .filter_map(move |(col, value)| value.map(|v| (row, col, v)))
+        })
+        .collect()
+}
+
+/// Tries removing the givens at `order` (with their symmetric ones), keeping each removal
+/// only if the solution stays unique.
+///
+/// A given that is needed stays needed once others are removed, so a single pass leaves
+/// only necessary givens.
+pub fn remove_redundant_givens<const N: usize>(
+    givens: &[Given],
+    order: &[(usize, usize)],
+    symmetry: Symmetry,
+) -> Vec<Given>
+where
+    ForSize<N>: StorageForSize,
+{
+    let mut grid = vec![vec![None; N]; N];
+    for &(row, col, value) in givens {
+        grid[row][col] = Some(value);
+    }
+
+    for &(row, col) in order {
+        if grid[row][col].is_none() {
+            continue;
+        }
+        let orbit = symmetry.orbit(N, row, col);
+        let removed: Vec<_> = orbit.iter().map(|&(r, c)| grid[r][c].take()).collect();
+        if !has_unique_solution::<N>(&grid_to_givens(&grid)) {
+            for (&(r, c), value) in orbit.iter().zip(removed) {
+                grid[r][c] = value;
+            }
+        }
+    }
+    grid_to_givens(&grid)
+}
+
+/// Reduces a puzzle until every remaining given is necessary for a unique solution.
+pub fn minimize<const N: usize>(givens: &[Given], symmetry: Symmetry) -> Result<Vec<Given>, String>
+where
+    ForSize<N>: StorageForSize,
+{
+    let board = SudokuBoard::<N>::from_givens(givens)?;
+    let solutions = DlxSolver::from_board(board).count_solutions(2)?;
+    if solutions != 1 {
+        return Err(format!(
+            "Only a puzzle with a unique solution can be minimized, found {}.",
+            if solutions == 0 { "none" } else { "several" }
+        ));
+    }
+
+    let order: Vec<_> = givens.iter().map(|&(row, col, _)| (row, col)).collect();
+    Ok(remove_redundant_givens::<N>(givens, &order, symmetry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_unique_solution, minimize};
+    use crate::generator::Symmetry;
+    use crate::puzzle::parse_puzzle;
+
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    #[test]
+    fn keeps_only_necessary_givens() {
+        let (_, givens) = parse_puzzle(SOLUTION).unwrap();
+        let minimized = minimize::<9>(&givens, Symmetry::None).unwrap();
+        assert!(minimized.len() < givens.len());
+        assert!(minimized.iter().all(|given| givens.contains(given)));
+        assert!(has_unique_solution::<9>(&minimized));
+        for given in &minimized {
+            let fewer: Vec<_> = minimized.iter().copied().filter(|g| g != given).collect();
+            assert!(!has_unique_solution::<9>(&fewer), "{given:?} is not needed");
+        }
+    }
+
+    #[test]
+    fn keeps_the_symmetry() {
+        let (_, givens) = parse_puzzle(SOLUTION).unwrap();
+        let minimized = minimize::<9>(&givens, Symmetry::Rotational).unwrap();
+        assert!(has_unique_solution::<9>(&minimized));
+        for &(row, col, _) in &minimized {
+            assert!(minimized
+                .iter()
+                .any(|&(r, c, _)| (r, c) == (8 - row, 8 - col)));
+        }
+    }
+
+    #[test]
+    fn rejects_puzzles_without_a_unique_solution() {
+        let (_, givens) = parse_puzzle(&SOLUTION.replace(['1', '2'], ".")).unwrap();
+        let err = minimize::<9>(&givens, Symmetry::None).unwrap_err();
+        assert!(err.contains("several"), "{err}");
+    }
+}
@@ -1,4 +1,4 @@
-use num_traits::PrimInt;
+use num_traits::{Bounded, One, PrimInt, Zero};
 use std::fmt::Debug;
 use std::ops::{BitAndAssign, BitOrAssign, Sub};
 
@@ -15,13 +15,47 @@ pub trait StorageForSize {
         + TryFrom<usize, Error: Debug>
         + From<u8>
         + PrimInt;
+
+    /// The mask of all the values `1..=size`, the lowest `size` bits.
+    ///
+    /// Shifting the full type right rather than `(1 << size) - 1` keeps it from overflowing
+    /// when `size` is the width of the type, like 16 in a `u16`.
+    fn full_mask(size: usize) -> Self::SType {
+        let bits = Self::SType::zero().count_zeros() as usize;
+        assert!(
+            size > 0 && size <= bits,
+            "Size {size} does not fit in {bits} bits"
+        );
+        Self::SType::max_value() >> (bits - size)
+    }
+
+    /// The mask of a single value, between 1 and the width of the type.
+    fn value_mask(value: usize) -> Self::SType {
+        Self::SType::one() << (value - 1)
+    }
 }
 
+#[derive(Clone)]
 pub struct ForSize<const N: usize>;
 
+impl StorageForSize for ForSize<4> {
+    type SType = u8;
+}
+impl StorageForSize for ForSize<6> {
+    type SType = u16;
+}
+impl StorageForSize for ForSize<8> {
+    type SType = u16;
+}
 impl StorageForSize for ForSize<9> {
     type SType = u16;
 }
+impl StorageForSize for ForSize<10> {
+    type SType = u16;
+}
+impl StorageForSize for ForSize<12> {
+    type SType = u16;
+}
 impl StorageForSize for ForSize<16> {
     type SType = u16;
 }
@@ -31,3 +65,15 @@ impl StorageForSize for ForSize<25> {
 impl StorageForSize for ForSize<36> {
     type SType = u64;
 }
+impl StorageForSize for ForSize<49> {
+    type SType = u64;
+}
+impl StorageForSize for ForSize<64> {
+    type SType = u64;
+}
+impl StorageForSize for ForSize<81> {
+    type SType = u128;
+}
+impl StorageForSize for ForSize<100> {
+    type SType = u128;
+}
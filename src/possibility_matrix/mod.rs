@@ -1,9 +1,11 @@
 pub mod bit_storage;
 pub mod possibility_iterator;
+pub mod transform;
 
 use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
 use crate::possibility_matrix::possibility_iterator::PossibilityIterator;
-use num_traits::{Bounded, One, Zero};
+use crate::region::default_box_shape;
+use num_traits::Zero;
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
 
@@ -36,12 +38,14 @@ where
     x != T::from(0) && (x & (x - T::from(1))) == T::from(0)
 }
 
+#[derive(Clone)]
 pub struct PossibilityMatrix<const N: usize, S: StorageForSize = ForSize<N>>
 where
     ForSize<N>: StorageForSize,
 {
     size: usize,
-    block_size: usize,
+    box_rows: usize,
+    box_cols: usize,
     board: [[S::SType; N]; N],
 }
 
@@ -50,10 +54,20 @@ where
     ForSize<N>: StorageForSize,
 {
     pub fn new() -> Self {
+        let (box_rows, box_cols) = default_box_shape(N);
+        Self::with_box_shape(box_rows, box_cols)
+    }
+
+    pub fn with_box_shape(box_rows: usize, box_cols: usize) -> Self {
+        assert!(
+            box_rows * box_cols == N,
+            "Invalid box shape {box_rows}x{box_cols} for size {N}"
+        );
         Self {
             size: N,
-            block_size: N.isqrt(),
-            board: [[<S::SType>::max_value(); N]; N],
+            box_rows,
+            box_cols,
+            board: [[S::full_mask(N); N]; N],
         }
     }
 
@@ -61,31 +75,32 @@ where
         self.size
     }
 
-    pub const fn block_size(&self) -> usize {
-        self.block_size
+    pub const fn box_rows(&self) -> usize {
+        self.box_rows
+    }
+
+    pub const fn box_cols(&self) -> usize {
+        self.box_cols
     }
 
     #[allow(clippy::inline_always)]
     #[inline(always)]
-    fn value_to_stype(&self, value: usize) -> S::SType {
+    fn value_mask(&self, value: usize) -> S::SType {
         assert_value!(self, value);
-        value.try_into().unwrap()
+        S::value_mask(value)
     }
 
     pub fn set(&mut self, row: usize, col: usize, value: usize) {
         assert_position!(self, row, col);
-        let value = self.value_to_stype(value);
-        self.board[row][col] = S::SType::one() << (value - S::SType::one());
+        self.board[row][col] = self.value_mask(value);
     }
 
-    #[allow(dead_code)]
     pub fn set_possible_values(&mut self, row: usize, col: usize, values: &[usize]) {
         assert_position!(self, row, col);
 
         self.board[row][col] = S::SType::zero();
         for &value in values {
-            let value = self.value_to_stype(value);
-            self.board[row][col] |= S::SType::one() << (value - S::SType::one());
+            self.board[row][col] |= self.value_mask(value);
         }
     }
 
@@ -95,16 +110,14 @@ where
 
         let mut mask = S::SType::zero();
         for &value in values {
-            let value = self.value_to_stype(value);
-            mask |= S::SType::one() << (value - S::SType::one());
+            mask |= self.value_mask(value);
         }
         self.board[row][col] &= mask;
     }
 
     pub fn remove_value(&mut self, row: usize, col: usize, value: usize) {
         assert_position!(self, row, col);
-        let value = self.value_to_stype(value);
-        self.board[row][col] &= !(S::SType::one() << (value - S::SType::one()));
+        self.board[row][col] &= !self.value_mask(value);
     }
 
     pub fn get_possible_values(&self, row: usize, col: usize) -> PossibilityIterator<N, S> {
@@ -114,14 +127,13 @@ where
 
     pub fn is_possible_value(&self, row: usize, col: usize, value: usize) -> bool {
         assert_position!(self, row, col);
-        assert_value!(self, value);
-        let value = self.value_to_stype(value);
-        (self.board[row][col] & (S::SType::one() << (value - S::SType::one()))) != S::SType::zero()
+        (self.board[row][col] & self.value_mask(value)) != S::SType::zero()
     }
 
     pub fn is_cell_resolved(&self, row: usize, col: usize) -> bool {
         assert_position!(self, row, col);
-        is_one_on_bit(((S::SType::one() << N) - S::SType::one()) & self.board[row][col])
+        // cells never hold bits above `full_mask`, see `with_box_shape`.
+        is_one_on_bit(self.board[row][col])
     }
 
     pub fn is_board_resolved(&self) -> bool {
@@ -135,10 +147,10 @@ where
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let cell_width = self.size * 2;
-        let line_width = (cell_width + 1) * self.block_size() + 1;
+        let line_width = (cell_width + 1) * self.box_cols() + 1;
 
         let write_horizontal_line = |f: &mut Formatter| -> fmt::Result {
-            for _ in 0..self.block_size() {
+            for _ in 0..self.size / self.box_cols() {
                 write!(f, "+")?;
                 for _ in 0..line_width {
                     write!(f, "-")?;
@@ -164,12 +176,12 @@ where
                 };
 
                 write!(f, "{value_string} ")?;
-                if (col + 1) % self.block_size() == 0 {
+                if (col + 1) % self.box_cols() == 0 {
                     write!(f, "| ")?;
                 }
             }
             writeln!(f)?;
-            if (row + 1) % self.block_size() == 0 {
+            if (row + 1) % self.box_rows() == 0 {
                 write_horizontal_line(f)?;
             }
         }
@@ -182,12 +194,13 @@ where
     ForSize<N>: StorageForSize,
 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let block_size = self.block_size();
+        let box_rows = self.box_rows();
+        let box_cols = self.box_cols();
         let cell_width = 3;
-        let line_width = cell_width * block_size;
+        let line_width = cell_width * box_cols;
 
         let write_horizontal_line = |f: &mut Formatter| -> fmt::Result {
-            for _ in 0..block_size {
+            for _ in 0..self.size / box_cols {
                 write!(f, "+")?;
                 for _ in 0..line_width {
                     write!(f, "-")?;
@@ -207,15 +220,73 @@ where
                     _ => write!(f, " _ ")?,
                 }
 
-                if (col + 1) % block_size == 0 {
+                if (col + 1) % box_cols == 0 {
                     write!(f, "|")?;
                 }
             }
             writeln!(f)?;
-            if (row + 1) % block_size == 0 {
+            if (row + 1) % box_rows == 0 {
                 write_horizontal_line(f)?;
             }
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::PossibilityMatrix;
+    use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+
+    fn check_full_width<const N: usize>()
+    where
+        ForSize<N>: StorageForSize,
+    {
+        let mut matrix = PossibilityMatrix::<N>::new();
+        assert!(!matrix.is_cell_resolved(0, 0));
+        assert_eq!(matrix.get_possible_values(0, 0).count(), N);
+        assert_eq!(matrix.get_possible_values(0, 0).last(), Some(N));
+
+        for value in 1..N {
+            matrix.remove_value(0, 0, value);
+        }
+        assert!(matrix.is_cell_resolved(0, 0));
+        assert_eq!(matrix.get_possible_values(0, 0).collect::<Vec<_>>(), [N]);
+
+        matrix.set(1, 1, N);
+        assert!(matrix.is_cell_resolved(1, 1));
+        assert!(matrix.is_possible_value(1, 1, N));
+        assert!(!matrix.is_possible_value(1, 1, 1));
+
+        matrix.remove_value(1, 1, N);
+        assert!(!matrix.is_cell_resolved(1, 1));
+        assert_eq!(matrix.get_possible_values(1, 1).count(), 0);
+    }
+
+    #[test]
+    fn full_width_masks() {
+        check_full_width::<4>();
+        check_full_width::<9>();
+        check_full_width::<16>();
+        check_full_width::<25>();
+        check_full_width::<64>();
+        check_full_width::<100>();
+    }
+
+    #[test]
+    fn resolves_cells_using_every_bit() {
+        // 16 and 64 fill their u16 and u64, where `(1 << size) - 1` overflowed.
+        assert_eq!(ForSize::<16>::full_mask(16), u16::MAX);
+        assert_eq!(ForSize::<64>::full_mask(64), u64::MAX);
+
+        let mut matrix = PossibilityMatrix::<16>::new();
+        assert!(!matrix.is_board_resolved());
+        matrix.set(0, 0, 16);
+        assert!(matrix.is_cell_resolved(0, 0));
+
+        let mut matrix = PossibilityMatrix::<64>::new();
+        assert!(!matrix.is_cell_resolved(0, 0));
+        matrix.set(0, 0, 64);
+        assert!(matrix.is_cell_resolved(0, 0));
+    }
+}
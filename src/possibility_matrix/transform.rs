@@ -0,0 +1,260 @@
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::possibility_matrix::PossibilityMatrix;
+use crate::puzzle::Given;
+use std::str::FromStr;
+
+/// A validity preserving transformation of a board.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Transform {
+    Transpose,
+    /// 90° clockwise.
+    Rotate,
+    /// Upside down.
+    ReflectRows,
+    /// Left to right.
+    ReflectCols,
+    /// `permutation[value - 1]` is the new value.
+    PermuteDigits(Vec<usize>),
+    /// Two rows of the same band.
+    SwapRows(usize, usize),
+    /// Two columns of the same stack.
+    SwapCols(usize, usize),
+    SwapBands(usize, usize),
+    SwapStacks(usize, usize),
+}
+
+impl FromStr for Transform {
+    type Err = String;
+
+    /// Parses `transpose`, `rotate`, `reflect-rows`, `reflect-cols`, `permute-digits:<VALUES>`
+    /// (e.g. `permute-digits:912345678`) and `swap-<rows|cols|bands|stacks>:<I>:<J>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid transform '{s}'.");
+        let parts: Vec<&str> = s.split(':').collect();
+        let index = |i: usize| -> Result<usize, String> {
+            parts
+                .get(i)
+                .and_then(|p| p.parse().ok())
+                .ok_or_else(invalid)
+        };
+
+        match parts[0] {
+            "transpose" => Ok(Transform::Transpose),
+            "rotate" => Ok(Transform::Rotate),
+            "reflect-rows" => Ok(Transform::ReflectRows),
+            "reflect-cols" => Ok(Transform::ReflectCols),
+            "permute-digits" => {
+                let values = parts.get(1).ok_or_else(invalid)?;
+                let permutation = if values.contains(',') {
+                    values.split(',').map(str::parse).collect::<Result<_, _>>()
+                } else {
+                    values.chars().map(|c| c.to_string().parse()).collect()
+                };
+                Ok(Transform::PermuteDigits(
+                    permutation.map_err(|_| invalid())?,
+                ))
+            }
+            "swap-rows" => Ok(Transform::SwapRows(index(1)?, index(2)?)),
+            "swap-cols" => Ok(Transform::SwapCols(index(1)?, index(2)?)),
+            "swap-bands" => Ok(Transform::SwapBands(index(1)?, index(2)?)),
+            "swap-stacks" => Ok(Transform::SwapStacks(index(1)?, index(2)?)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl<const N: usize, S: StorageForSize> PossibilityMatrix<N, S>
+where
+    ForSize<N>: StorageForSize,
+{
+    pub fn transform(&mut self, transform: &Transform) -> Result<(), String> {
+        if matches!(transform, Transform::Transpose | Transform::Rotate)
+            && self.box_rows != self.box_cols
+        {
+            return Err(format!(
+                "{transform:?} would turn the {}x{} boxes sideways.",
+                self.box_rows, self.box_cols
+            ));
+        }
+
+        match transform {
+            Transform::Transpose => self.transpose(),
+            Transform::Rotate => self.rotate(),
+            Transform::ReflectRows => self.board.reverse(),
+            Transform::ReflectCols => self.board.iter_mut().for_each(|row| row.reverse()),
+            Transform::PermuteDigits(permutation) => self.permute_digits(permutation)?,
+            &Transform::SwapRows(i, j) => {
+                self.assert_same_block(i, j, self.box_rows)?;
+                self.board.swap(i, j);
+            }
+            &Transform::SwapCols(i, j) => {
+                self.assert_same_block(i, j, self.box_cols)?;
+                self.board.iter_mut().for_each(|row| row.swap(i, j));
+            }
+            &Transform::SwapBands(i, j) => {
+                let lines = self.box_rows;
+                self.assert_valid_blocks(i, j, lines)?;
+                for k in 0..lines {
+                    self.board.swap(i * lines + k, j * lines + k);
+                }
+            }
+            &Transform::SwapStacks(i, j) => {
+                let lines = self.box_cols;
+                self.assert_valid_blocks(i, j, lines)?;
+                for k in 0..lines {
+                    for row in &mut self.board {
+                        row.swap(i * lines + k, j * lines + k);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn transpose(&mut self) {
+        for row in 0..self.size {
+            for col in row + 1..self.size {
+                let cell = self.board[row][col];
+                self.board[row][col] = self.board[col][row];
+                self.board[col][row] = cell;
+            }
+        }
+    }
+
+    fn rotate(&mut self) {
+        // a clockwise rotation is a transpose followed by mirroring the rows.
+        self.transpose();
+        self.board.iter_mut().for_each(|row| row.reverse());
+    }
+
+    fn permute_digits(&mut self, permutation: &[usize]) -> Result<(), String> {
+        let mut sorted = permutation.to_vec();
+        sorted.sort_unstable();
+        if !sorted.iter().copied().eq(1..=self.size) {
+            return Err(format!(
+                "{permutation:?} is not a permutation of the values 1 to {}.",
+                self.size
+            ));
+        }
+
+        for row in 0..self.size {
+            for col in 0..self.size {
+                let values: Vec<_> = self
+                    .get_possible_values(row, col)
+                    .map(|value| permutation[value - 1])
+                    .collect();
+                self.set_possible_values(row, col, &values);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the lines are in the same band (stack) of `block_lines` lines.
+    fn assert_same_block(&self, i: usize, j: usize, block_lines: usize) -> Result<(), String> {
+        if i >= self.size || j >= self.size || i / block_lines != j / block_lines {
+            return Err(format!("Lines {i} and {j} are not in the same block."));
+        }
+        Ok(())
+    }
+
+    fn assert_valid_blocks(&self, i: usize, j: usize, block_lines: usize) -> Result<(), String> {
+        let blocks = self.size / block_lines;
+        if i >= blocks || j >= blocks {
+            return Err(format!(
+                "Invalid blocks {i} and {j}, expected less than {blocks}."
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Applies the transforms to a puzzle's givens.
+pub fn transform_givens<const N: usize>(
+    givens: &[Given],
+    transforms: &[Transform],
+) -> Result<Vec<Given>, String>
+where
+    ForSize<N>: StorageForSize,
+{
+    let mut matrix = PossibilityMatrix::<N>::new();
+    for &(row, col, value) in givens {
+        matrix.set(row, col, value);
+    }
+    for transform in transforms {
+        matrix.transform(transform)?;
+    }
+
+    Ok((0..N)
+        .flat_map(|row| (0..N).map(move |col| (row, col)))
+        .filter(|&(row, col)| matrix.is_cell_resolved(row, col))
+        .map(|(row, col)| {
+            (
+                row,
+                col,
+                matrix.get_possible_values(row, col).next().unwrap(),
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{transform_givens, Transform};
+    use crate::solver::SudokuSolver;
+    use crate::{KNOWN_VALUES, KNOWN_VALUES3};
+
+    fn solve(givens: &[(usize, usize, usize)]) -> crate::board::SudokuBoard<9> {
+        let mut solver = SudokuSolver::<9>::new();
+        for &(row, col, value) in givens {
+            solver.set(row, col, value);
+        }
+        solver.solve().unwrap()
+    }
+
+    #[test]
+    fn solving_commutes_with_transforms() {
+        let transforms = [
+            Transform::Transpose,
+            Transform::Rotate,
+            Transform::ReflectRows,
+            Transform::ReflectCols,
+            Transform::PermuteDigits(vec![9, 1, 2, 3, 4, 5, 6, 7, 8]),
+            Transform::SwapRows(3, 5),
+            Transform::SwapCols(6, 8),
+            Transform::SwapBands(0, 2),
+            Transform::SwapStacks(1, 2),
+        ];
+
+        for givens in [&KNOWN_VALUES[..], &KNOWN_VALUES3[..]] {
+            for transform in &transforms {
+                let mut board = solve(givens);
+                board.transform(transform).unwrap();
+                let transformed_givens =
+                    transform_givens::<9>(givens, std::slice::from_ref(transform)).unwrap();
+
+                assert_eq!(
+                    format!("{board:?}"),
+                    format!("{:?}", solve(&transformed_givens)),
+                    "{transform:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_transforms() {
+        for transform in [
+            Transform::SwapRows(2, 3),
+            Transform::SwapCols(0, 9),
+            Transform::SwapBands(0, 3),
+            Transform::PermuteDigits(vec![1, 1, 2, 3, 4, 5, 6, 7, 8]),
+        ] {
+            assert!(transform_givens::<9>(&KNOWN_VALUES, &[transform]).is_err());
+        }
+        assert_eq!(
+            "swap-stacks:0:2".parse::<Transform>(),
+            Ok(Transform::SwapStacks(0, 2))
+        );
+        assert!("swap-stacks:0".parse::<Transform>().is_err());
+    }
+}
@@ -0,0 +1,593 @@
+use crate::board::Parity;
+use crate::region::RegionLayout;
+use crate::solver::adjacency::{kropki, Adjacency, Relation};
+use crate::solver::gattai::GattaiLayout;
+use crate::solver::killer_cage::Cage;
+use crate::solver::line::Line;
+use crate::solver::outside_clue::OutsideClue;
+
+/// A known value, as `(row, col, value)`.
+pub type Given = (usize, usize, usize);
+
+/// Sizes up to this one are written one symbol per cell (`1-9` then `A-Z`),
+/// larger ones as whitespace separated numbers.
+const MAX_SYMBOL_SIZE: usize = 35;
+
+fn value_to_symbol(value: usize) -> char {
+    match value {
+        1..=9 => char::from_digit(value as u32, 10).unwrap(),
+        _ => (b'A' + (value - 10) as u8) as char,
+    }
+}
+
+fn parse_symbol(symbol: char, size: usize) -> Result<Option<usize>, String> {
+    let value = match symbol {
+        '.' | '0' => return Ok(None),
+        '1'..='9' => symbol as usize - '0' as usize,
+        'A'..='Z' | 'a'..='z' => symbol.to_ascii_uppercase() as usize - 'A' as usize + 10,
+        _ => return Err(format!("Invalid cell '{symbol}'.")),
+    };
+    if value > size {
+        return Err(format!(
+            "Invalid value {value} expected between 1 and {size}."
+        ));
+    }
+    Ok(Some(value))
+}
+
+/// Parses the whitespace separated numbers format, `None` if `tokens` isn't one.
+fn parse_numbers(tokens: &[&str]) -> Option<Vec<Option<usize>>> {
+    let size = tokens.len().isqrt();
+    if size == 0 || size * size != tokens.len() {
+        return None;
+    }
+    tokens
+        .iter()
+        .map(|&token| match token {
+            "." | "0" => Some(None),
+            _ => token
+                .parse()
+                .ok()
+                .filter(|v| (1..=size).contains(v))
+                .map(Some),
+        })
+        .collect()
+}
+
+/// Reads a puzzle written by `to_puzzle_string` (whitespace is ignored in the symbol format),
+/// returning its size and givens.
+pub fn parse_puzzle(text: &str) -> Result<(usize, Vec<Given>), String> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let cells = match parse_numbers(&tokens) {
+        Some(cells) => cells,
+        None => {
+            let symbols: Vec<char> = tokens.concat().chars().collect();
+            let size = symbols.len().isqrt();
+            if size == 0 || size * size != symbols.len() {
+                return Err(format!(
+                    "A puzzle must have a square number of cells, found {}.",
+                    symbols.len()
+                ));
+            }
+            symbols
+                .into_iter()
+                .map(|symbol| parse_symbol(symbol, size))
+                .collect::<Result<_, _>>()?
+        }
+    };
+
+    let size = cells.len().isqrt();
+    let givens = cells
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, cell)| cell.map(|value| (i / size, i % size, value)))
+        .collect();
+    Ok((size, givens))
+}
+
+/// Writes the givens as a single line, `.` marking the empty cells.
+pub fn to_puzzle_string(size: usize, givens: &[Given]) -> String {
+    let mut cells = vec![None; size * size];
+    for &(row, col, value) in givens {
+        cells[row * size + col] = Some(value);
+    }
+
+    if size <= MAX_SYMBOL_SIZE {
+        cells
+            .into_iter()
+            .map(|cell| cell.map_or('.', value_to_symbol))
+            .collect()
+    } else {
+        cells
+            .into_iter()
+            .map(|cell| cell.map_or(".".to_string(), |v| v.to_string()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Reads a label per cell written like the cells of a puzzle: one symbol per cell, or whitespace
+/// separated labels for sizes above `MAX_SYMBOL_SIZE`.
+fn parse_labels(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let size = tokens.len().isqrt();
+    let labels: Vec<String> = if size > MAX_SYMBOL_SIZE && size * size == tokens.len() {
+        tokens.into_iter().map(str::to_string).collect()
+    } else {
+        tokens.concat().chars().map(String::from).collect()
+    };
+
+    let size = labels.len().isqrt();
+    if size == 0 || size * size != labels.len() {
+        return Err(format!(
+            "A layout must have a square number of cells, found {}.",
+            labels.len()
+        ));
+    }
+    Ok(labels.chunks(size).map(<[String]>::to_vec).collect())
+}
+
+/// Reads a jigsaw layout, the label of the box of every cell in the `parse_labels` format.
+pub fn parse_layout(text: &str) -> Result<RegionLayout, String> {
+    RegionLayout::from_grid(&parse_labels(text)?)
+}
+
+/// Writes the label of the box of every cell, one row per line, for `parse_layout`.
+pub fn to_layout_string(layout: &RegionLayout) -> String {
+    let size = layout.size();
+    (0..size)
+        .map(|row| {
+            let labels = (0..size).map(|col| layout.box_index(row, col) + 1);
+            if size <= MAX_SYMBOL_SIZE {
+                labels.map(value_to_symbol).collect()
+            } else {
+                labels
+                    .map(|label| label.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Reads extra regions in the `parse_labels` format, `.` marking the cells outside of them,
+/// and adds them to the layout.
+pub fn parse_extra_regions(text: &str, layout: RegionLayout) -> Result<RegionLayout, String> {
+    let grid = parse_labels(text)?;
+    if grid.len() != layout.size() {
+        return Err(format!(
+            "The extra regions are for size {} but the board is of size {}.",
+            grid.len(),
+            layout.size()
+        ));
+    }
+
+    let mut labels: Vec<&String> = Vec::new();
+    let mut regions: Vec<Vec<(usize, usize)>> = Vec::new();
+    for (row, cells) in grid.iter().enumerate() {
+        for (col, label) in cells.iter().enumerate().filter(|(_, label)| *label != ".") {
+            if let Some(i) = labels.iter().position(|&known| known == label) {
+                regions[i].push((row, col));
+            } else {
+                labels.push(label);
+                regions.push(vec![(row, col)]);
+            }
+        }
+    }
+    regions
+        .into_iter()
+        .try_fold(layout, |layout, region| layout.with_extra_region(region))
+}
+
+/// Reads a cell written like `r1c2`, rows and columns counting from 1.
+pub fn parse_cell(text: &str, size: usize) -> Result<(usize, usize), String> {
+    let invalid = || format!("Invalid cell '{text}', expected something like r1c2.");
+    let lowercase = text.to_ascii_lowercase();
+    let (row, col) = lowercase
+        .strip_prefix('r')
+        .and_then(|rest| rest.split_once('c'))
+        .ok_or_else(invalid)?;
+    let row: usize = row.parse().map_err(|_| invalid())?;
+    let col: usize = col.parse().map_err(|_| invalid())?;
+    if row == 0 || col == 0 || row > size || col > size {
+        return Err(format!(
+            "Invalid cell '{text}', rows and columns go from 1 to {size}."
+        ));
+    }
+    Ok((row - 1, col - 1))
+}
+
+/// Writes a cell like `parse_cell` reads it.
+pub fn to_cell_string((row, col): (usize, usize)) -> String {
+    format!("r{}c{}", row + 1, col + 1)
+}
+
+/// Reads killer cages, one per line as its sum followed by its cells (`15 r1c1 r1c2 r2c1`).
+/// Empty lines and lines starting with `#` are ignored.
+pub fn parse_cages(text: &str, size: usize) -> Result<Vec<Cage>, String> {
+    let mut cages: Vec<Cage> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut tokens = line.split_whitespace();
+        let sum = tokens.next().unwrap_or_default();
+        let sum = sum
+            .parse()
+            .map_err(|_| format!("Invalid cage sum '{sum}' in '{line}'."))?;
+        let cells = tokens
+            .map(|cell| parse_cell(cell, size))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(cell) = cells
+            .iter()
+            .find(|cell| cages.iter().any(|cage| cage.cells.contains(cell)))
+        {
+            return Err(format!(
+                "The cell {cell:?} of '{line}' is already in a cage."
+            ));
+        }
+        cages.push(Cage::new(sum, cells, size)?);
+    }
+    Ok(cages)
+}
+
+/// Reads Kropki dots, one per line as `white` (consecutive values) or `black` (one value twice
+/// the other) followed by two adjacent cells (`white r1c1 r1c2`). A `negative` line adds the
+/// negative constraint: adjacent cells without a dot have neither relation.
+/// Empty lines and lines starting with `#` are ignored.
+pub fn parse_dots(text: &str, size: usize) -> Result<Vec<Adjacency>, String> {
+    let mut dots: Vec<Adjacency> = Vec::new();
+    let mut negative = false;
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let tokens: Vec<_> = line.split_whitespace().collect();
+        let relation = match tokens[0].to_ascii_lowercase().as_str() {
+            "negative" if tokens.len() == 1 => {
+                negative = true;
+                continue;
+            }
+            "white" => Relation::Consecutive,
+            "black" => Relation::Double,
+            _ => return Err(format!("Invalid dot '{line}', expected white or black.")),
+        };
+        let [first, second] = tokens[1..] else {
+            return Err(format!("A dot joins two cells, found '{line}'."));
+        };
+        let dot = Adjacency::new(
+            parse_cell(first, size)?,
+            parse_cell(second, size)?,
+            relation,
+        )?;
+        if dots.iter().any(|other| other.cells == dot.cells) {
+            return Err(format!("The cells of '{line}' already have a dot."));
+        }
+        dots.push(dot);
+    }
+    Ok(kropki(dots, negative, size))
+}
+
+/// Reads odd and even cells in the `parse_labels` format: `o` for odd, `e` for even and `.` for
+/// the other cells.
+pub fn parse_parity(text: &str, size: usize) -> Result<Vec<(usize, usize, Parity)>, String> {
+    let grid = parse_labels(text)?;
+    if grid.len() != size {
+        return Err(format!(
+            "The odd and even cells are for size {} but the board is of size {size}.",
+            grid.len()
+        ));
+    }
+
+    let mut cells = Vec::new();
+    for (row, labels) in grid.iter().enumerate() {
+        for (col, label) in labels.iter().enumerate() {
+            let parity = match label.to_ascii_lowercase().as_str() {
+                "o" => Parity::Odd,
+                "e" => Parity::Even,
+                "." => continue,
+                _ => {
+                    return Err(format!(
+                        "Invalid parity '{label}' at ({row},{col}), expected o, e or '.'."
+                    ))
+                }
+            };
+            cells.push((row, col, parity));
+        }
+    }
+    Ok(cells)
+}
+
+/// Reads greater-than signs between adjacent cells, one per line (`r1c1 > r1c2`, `r2c1 < r3c1`).
+/// Empty lines and lines starting with `#` are ignored.
+pub fn parse_inequalities(text: &str, size: usize) -> Result<Vec<Adjacency>, String> {
+    let mut signs: Vec<Adjacency> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let tokens: Vec<_> = line.split_whitespace().collect();
+        let [first, sign, second] = tokens[..] else {
+            return Err(format!("Invalid sign '{line}', expected r1c1 > r1c2."));
+        };
+        let relation = match sign {
+            ">" => Relation::Greater,
+            "<" => Relation::Less,
+            _ => {
+                return Err(format!(
+                    "Invalid sign '{sign}' in '{line}', expected > or <."
+                ))
+            }
+        };
+        let sign = Adjacency::new(
+            parse_cell(first, size)?,
+            parse_cell(second, size)?,
+            relation,
+        )?;
+        if signs.iter().any(|other| other.cells == sign.cells) {
+            return Err(format!("The cells of '{line}' already have a sign."));
+        }
+        signs.push(sign);
+    }
+    Ok(signs)
+}
+
+/// Reads lines and shapes, one per line as their kind followed by their cells: `thermo` from the
+/// bulb (`thermo r1c1 r1c2 r2c3`), `arrow` from the circle (`arrow r1c1 r2c2 r3c3`), `x` or `v`
+/// for adjacent cells adding up to 10 or 5 (`x r1c1 r1c2`), and `whisper`, `renban` or
+/// `palindrome` lines (`renban r1c1 r2c1 r3c2`).
+/// Empty lines and lines starting with `#` are ignored.
+pub fn parse_lines(text: &str, size: usize) -> Result<Vec<Line>, String> {
+    let mut lines = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut tokens = line.split_whitespace();
+        let kind = tokens.next().unwrap_or_default().to_ascii_lowercase();
+        let cells = tokens
+            .map(|cell| parse_cell(cell, size))
+            .collect::<Result<Vec<_>, _>>()?;
+        lines.push(Line::from_kind(&kind, cells, size)?);
+    }
+    Ok(lines)
+}
+
+/// Reads outside clues, one per line: `sandwich` followed by a row or a column and its sum
+/// (`sandwich r3 15`, `sandwich c5 0`), and `little-killer` followed by the first cell of the
+/// diagonal, its direction (`ne`, `nw`, `se` or `sw`) and its sum (`little-killer r1c3 se 15`).
+/// Empty lines and lines starting with `#` are ignored.
+pub fn parse_outside_clues(text: &str, size: usize) -> Result<Vec<OutsideClue>, String> {
+    let mut clues = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let tokens: Vec<_> = line.split_whitespace().collect();
+        let parse_sum = |sum: &str| -> Result<usize, String> {
+            sum.parse()
+                .map_err(|_| format!("Invalid clue sum '{sum}' in '{line}'."))
+        };
+        let parse_index = |index: &str| -> Result<usize, String> {
+            match index.parse::<usize>() {
+                Ok(index) if (1..=size).contains(&index) => Ok(index - 1),
+                _ => Err(format!("Invalid row or column in '{line}'.")),
+            }
+        };
+        clues.push(match tokens.as_slice() {
+            [kind, line_name, sum] if kind.eq_ignore_ascii_case("sandwich") => {
+                let sum = parse_sum(sum)?;
+                let lowercase = line_name.to_ascii_lowercase();
+                if let Some(row) = lowercase.strip_prefix('r') {
+                    OutsideClue::row_sandwich(parse_index(row)?, sum, size)?
+                } else if let Some(col) = lowercase.strip_prefix('c') {
+                    OutsideClue::col_sandwich(parse_index(col)?, sum, size)?
+                } else {
+                    return Err(format!("Invalid row or column in '{line}'."));
+                }
+            }
+            [kind, start, direction, sum] if kind.eq_ignore_ascii_case("little-killer") => {
+                let direction = match direction.to_ascii_lowercase().as_str() {
+                    "ne" => (-1, 1),
+                    "nw" => (-1, -1),
+                    "se" => (1, 1),
+                    "sw" => (1, -1),
+                    _ => return Err(format!("Invalid direction in '{line}'.")),
+                };
+                OutsideClue::little_killer(
+                    parse_cell(start, size)?,
+                    direction,
+                    parse_sum(sum)?,
+                    size,
+                )?
+            }
+            _ => {
+                return Err(format!(
+                    "Invalid clue '{line}', expected sandwich r3 15 or little-killer r1c3 se 15."
+                ))
+            }
+        });
+    }
+    Ok(clues)
+}
+
+/// Reads a gattai puzzle, one line per grid of `layout` in its order, each written like a
+/// regular puzzle, returning the givens on the composite grid. Shared cells may be given by any
+/// of their grids, but not with different values.
+/// Empty lines and lines starting with `#` are ignored.
+pub fn parse_gattai(text: &str, layout: &GattaiLayout) -> Result<Vec<Given>, String> {
+    let puzzles: Vec<_> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    if puzzles.len() != layout.corners().len() {
+        return Err(format!(
+            "Expected a puzzle for each of the {} grids, found {}.",
+            layout.corners().len(),
+            puzzles.len()
+        ));
+    }
+
+    let mut givens: Vec<Given> = Vec::new();
+    for (puzzle, &(top, left)) in puzzles.into_iter().zip(layout.corners()) {
+        let (size, grid_givens) = parse_puzzle(puzzle)?;
+        if size != layout.size() {
+            return Err(format!(
+                "Expected {0}x{0} grids, found a {size}x{size} one.",
+                layout.size()
+            ));
+        }
+        for (row, col, value) in grid_givens {
+            let given = (top + row, left + col, value);
+            match givens
+                .iter()
+                .find(|other| (other.0, other.1) == (given.0, given.1))
+            {
+                Some(other) if other.2 != value => {
+                    return Err(format!(
+                        "The shared cell ({},{}) is given as {} and {value}.",
+                        given.0, given.1, other.2
+                    ));
+                }
+                Some(_) => {}
+                None => givens.push(given),
+            }
+        }
+    }
+    Ok(givens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_cages, parse_cell, parse_dots, parse_gattai, parse_inequalities, parse_lines,
+        parse_outside_clues, parse_parity, parse_puzzle, to_cell_string,
+    };
+    use crate::board::Parity;
+    use crate::solver::adjacency::Relation;
+    use crate::solver::gattai::GattaiLayout;
+    use crate::solver::line::Line;
+    use crate::solver::outside_clue::OutsideClue;
+
+    #[test]
+    fn parses_numbers() {
+        let (size, givens) = parse_puzzle("1 . 0 2 . . . . . . . . . . . 4").unwrap();
+        assert_eq!(size, 4);
+        assert_eq!(givens, [(0, 0, 1), (0, 3, 2), (3, 3, 4)]);
+        assert!(parse_puzzle("00 1 2 3 4 . . . . . . . . . . .").is_err());
+        assert!(parse_puzzle("5 . . . . . . . . . . . . . . .").is_err());
+    }
+
+    #[test]
+    fn parses_cells() {
+        assert_eq!(parse_cell("r1c2", 9), Ok((0, 1)));
+        assert_eq!(parse_cell("R12C16", 16), Ok((11, 15)));
+        assert!(parse_cell("r0c1", 9).is_err());
+        assert!(parse_cell("r1c10", 9).is_err());
+        assert!(parse_cell("c1r1", 9).is_err());
+        assert_eq!(to_cell_string((11, 15)), "r12c16");
+    }
+
+    #[test]
+    fn parses_cages() {
+        let cages = parse_cages("# a comment\n\n3 r1c1 r1c2\n 17 r2c1 r2c2 r3c1 \n", 9).unwrap();
+        assert_eq!(cages.len(), 2);
+        assert_eq!(cages[0].sum, 3);
+        assert_eq!(cages[1].cells, [(1, 0), (1, 1), (2, 0)]);
+
+        assert!(parse_cages("3 r1c1 r1c2\n4 r1c2 r1c3", 9).is_err());
+        assert!(parse_cages("x r1c1", 9).is_err());
+    }
+
+    #[test]
+    fn parses_dots() {
+        let dots = parse_dots("# dots\nwhite r1c1 r1c2\nBLACK r2c1 r1c1\n", 9).unwrap();
+        assert_eq!(dots.len(), 2);
+        assert_eq!(dots[1].cells, ((0, 0), (1, 0)));
+        assert_eq!(dots[1].relation, Relation::Double);
+        assert_eq!(
+            parse_dots("negative\nwhite r1c1 r1c2", 9).unwrap().len(),
+            2 * 9 * 8
+        );
+
+        assert!(parse_dots("white r1c1 r2c2", 9).is_err());
+        assert!(parse_dots("white r1c1 r1c2\nblack r1c2 r1c1", 9).is_err());
+        assert!(parse_dots("grey r1c1 r1c2", 9).is_err());
+    }
+
+    #[test]
+    fn parses_lines() {
+        let text = "# lines\nthermo r1c1 r1c2 r2c3\nARROW r5c5 r4c4 r3c4\nx r9c8 r9c9\nv r1c9 r2c9";
+        let lines = parse_lines(text, 9).unwrap();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], Line::Thermo(vec![(0, 0), (0, 1), (1, 2)]));
+        assert_eq!(lines[1].cells(), [(4, 4), (3, 3), (2, 3)]);
+        assert_eq!(lines[3], Line::xv((0, 8), (1, 8), 5, 9).unwrap());
+
+        assert!(parse_lines("arrow r1c1", 9).is_err());
+        assert!(parse_lines("x r1c1 r1c2 r1c3", 9).is_err());
+        assert!(parse_lines("zipper r1c1 r1c2", 9).is_err());
+
+        let text = "whisper r1c1 r2c2\nrenban r3c3 r3c4 r3c5\npalindrome r5c5 r6c6 r7c7";
+        let lines = parse_lines(text, 9).unwrap();
+        assert_eq!(lines[0], Line::Whisper(vec![(0, 0), (1, 1)]));
+        assert_eq!(lines[1], Line::Renban(vec![(2, 2), (2, 3), (2, 4)]));
+        assert_eq!(lines[2], Line::Palindrome(vec![(4, 4), (5, 5), (6, 6)]));
+    }
+
+    #[test]
+    fn parses_gattai() {
+        let layout = GattaiLayout::new(4, vec![(0, 0), (2, 2)]).unwrap();
+        let givens = parse_gattai("1...............\n\n..........2....3", &layout).unwrap();
+        assert_eq!(givens, [(0, 0, 1), (4, 4, 2), (5, 5, 3)]);
+        // the last cell of the first grid is the sixth one of the second.
+        assert!(parse_gattai("...............1\n.....1..........", &layout).is_ok());
+        assert!(parse_gattai("...............1\n.....2..........", &layout).is_err());
+        assert!(parse_gattai("1...............", &layout).is_err());
+        assert!(parse_gattai("1...............\n.........", &layout).is_err());
+    }
+
+    #[test]
+    fn parses_outside_clues() {
+        let text = "# clues\nsandwich r3 15\nSandwich C1 0\nlittle-killer r1c7 SE 4";
+        let clues = parse_outside_clues(text, 9).unwrap();
+        assert_eq!(clues.len(), 3);
+        assert_eq!(clues[0], OutsideClue::row_sandwich(2, 15, 9).unwrap());
+        assert_eq!(clues[1].cells()[8], (8, 0));
+        assert_eq!(clues[2].cells(), [(0, 6), (1, 7), (2, 8)]);
+
+        assert!(parse_outside_clues("sandwich r10 5", 9).is_err());
+        assert!(parse_outside_clues("sandwich x1 5", 9).is_err());
+        assert!(parse_outside_clues("little-killer r1c7 s 4", 9).is_err());
+        assert!(parse_outside_clues("little-killer r1c7 se", 9).is_err());
+    }
+
+    #[test]
+    fn parses_parity() {
+        let cells = parse_parity("o...\n..E.\n....\n...o", 4).unwrap();
+        assert_eq!(
+            cells,
+            [
+                (0, 0, Parity::Odd),
+                (1, 2, Parity::Even),
+                (3, 3, Parity::Odd)
+            ]
+        );
+        assert!(parse_parity("o..x............", 4).is_err());
+        assert!(parse_parity("o........", 4).is_err());
+    }
+
+    #[test]
+    fn parses_inequalities() {
+        let signs = parse_inequalities("# signs\nr1c1 > r1c2\nr3c1 < r2c1", 9).unwrap();
+        assert_eq!(signs[0].relation, Relation::Greater);
+        assert_eq!(signs[1].cells, ((1, 0), (2, 0)));
+        assert_eq!(signs[1].relation, Relation::Greater);
+
+        assert!(parse_inequalities("r1c1 = r1c2", 9).is_err());
+        assert!(parse_inequalities("r1c1 > r2c2", 9).is_err());
+        assert!(parse_inequalities("r1c1 > r1c2\nr1c2 > r1c1", 9).is_err());
+    }
+}
@@ -1,26 +1,45 @@
 use cached::proc_macro::cached;
+use std::fmt::Debug;
+use std::rc::Rc;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum RegionType {
     Row,
     Col,
     Box,
+    Diagonal,
+    Extra,
+    DisjointGroup,
+    Window,
+}
+
+/// The kind of a region and its cells.
+pub type Region = (RegionType, Vec<(usize, usize)>);
+
+/// The box shape used for a size when none is given, as `(box_rows, box_cols)`:
+/// square boxes when possible, otherwise the closest to square, wider than tall (2x3 for 6).
+pub fn default_box_shape(size: usize) -> (usize, usize) {
+    let box_rows = (1..=size.isqrt())
+        .rev()
+        .find(|&rows| size.is_multiple_of(rows))
+        .unwrap_or(1);
+    (box_rows, size / box_rows)
 }
 
 #[cached]
-pub fn get_all_boxes(size: usize) -> Vec<Vec<(usize, usize)>> {
+pub fn get_all_boxes(box_rows: usize, box_cols: usize) -> Vec<Vec<(usize, usize)>> {
+    let size = box_rows * box_cols;
     let mut regions = Vec::with_capacity(size);
-    let block_size = size.isqrt();
 
-    let box_vector_map: Vec<(usize, usize)> = (0..block_size)
-        .flat_map(|i| (0..block_size).map(|j| (i, j)).collect::<Vec<_>>())
+    let box_vector_map: Vec<(usize, usize)> = (0..box_rows)
+        .flat_map(|i| (0..box_cols).map(|j| (i, j)).collect::<Vec<_>>())
         .collect();
 
-    for bi in 0..block_size {
-        for bj in 0..block_size {
+    for bi in 0..box_cols {
+        for bj in 0..box_rows {
             let box_region = box_vector_map
                 .iter()
-                .map(|&(i, j)| (bi * block_size + i, bj * block_size + j))
+                .map(|&(i, j)| (bi * box_rows + i, bj * box_cols + j))
                 .collect();
             regions.push(box_region);
         }
@@ -28,19 +47,484 @@ pub fn get_all_boxes(size: usize) -> Vec<Vec<(usize, usize)>> {
     regions
 }
 
-#[cached]
-pub fn get_all_regions(size: usize) -> Vec<(RegionType, Vec<(usize, usize)>)> {
-    let mut regions = Vec::with_capacity(size * 3);
+/// Chess variants: a value can't repeat a knight's or a king's move away.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChessConstraint {
+    AntiKnight,
+    AntiKing,
+}
 
-    for i in 0..size {
-        regions.push((RegionType::Row, (0..size).map(|j| (i, j)).collect()));
-        regions.push((RegionType::Col, (0..size).map(|j| (j, i)).collect()));
+impl ChessConstraint {
+    const fn moves(self) -> &'static [(isize, isize)] {
+        match self {
+            ChessConstraint::AntiKnight => &[
+                (-2, -1),
+                (-2, 1),
+                (-1, -2),
+                (-1, 2),
+                (1, -2),
+                (1, 2),
+                (2, -1),
+                (2, 1),
+            ],
+            ChessConstraint::AntiKing => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+        }
     }
+}
 
-    regions.extend(
-        get_all_boxes(size)
-            .into_iter()
-            .map(|v| (RegionType::Box, v)),
-    );
-    regions
+/// The boxes of a board, rectangular ones or the arbitrary connected shapes of a jigsaw,
+/// the regions some variants add to the rows, columns and boxes, and the chess constraints
+/// adding peers to every cell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionLayout {
+    size: usize,
+    box_shape: Option<(usize, usize)>,
+    boxes: Vec<Vec<(usize, usize)>>,
+    box_index: Vec<Vec<usize>>,
+    extra_regions: Vec<(RegionType, Vec<(usize, usize)>)>,
+    chess_constraints: Vec<ChessConstraint>,
+    /// Every region, built again only when one is added.
+    regions: Rc<[Region]>,
+    /// The indices in `regions` of the regions of every cell.
+    cell_regions: Vec<Vec<Vec<usize>>>,
+    /// The cells a chess move away from every cell.
+    chess_peers: Vec<Vec<Vec<(usize, usize)>>>,
+}
+
+impl RegionLayout {
+    /// The boxes of `default_box_shape`.
+    pub fn for_size(size: usize) -> Self {
+        let (box_rows, box_cols) = default_box_shape(size);
+        Self::with_box_shape(box_rows, box_cols)
+    }
+
+    /// Boxes of `box_rows` by `box_cols` cells.
+    pub fn with_box_shape(box_rows: usize, box_cols: usize) -> Self {
+        let mut layout = Self::from_boxes(get_all_boxes(box_rows, box_cols));
+        layout.box_shape = Some((box_rows, box_cols));
+        layout
+    }
+
+    fn from_boxes(boxes: Vec<Vec<(usize, usize)>>) -> Self {
+        let size = boxes.len();
+        let mut box_index = vec![vec![0; size]; size];
+        for (i, box_) in boxes.iter().enumerate() {
+            for &(row, col) in box_ {
+                box_index[row][col] = i;
+            }
+        }
+        let mut layout = Self {
+            size,
+            box_shape: None,
+            boxes,
+            box_index,
+            extra_regions: Vec::new(),
+            chess_constraints: Vec::new(),
+            regions: Rc::new([]),
+            cell_regions: Vec::new(),
+            chess_peers: Vec::new(),
+        };
+        layout.index();
+        layout
+    }
+
+    fn push_extra_region(&mut self, region_type: RegionType, region: Vec<(usize, usize)>) {
+        self.extra_regions.push((region_type, region));
+        self.index();
+    }
+
+    /// Builds the regions and the peers of every cell again.
+    fn index(&mut self) {
+        self.regions = self.build_regions().into();
+        self.cell_regions = vec![vec![Vec::new(); self.size]; self.size];
+        for (i, (_, region)) in self.regions.iter().enumerate() {
+            for &(row, col) in region {
+                self.cell_regions[row][col].push(i);
+            }
+        }
+        self.chess_peers = (0..self.size)
+            .map(|row| {
+                (0..self.size)
+                    .map(|col| self.find_chess_peers(row, col))
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// A jigsaw layout from the box of every cell, given by any label.
+    ///
+    /// Every box must have as many cells as the size of the board and be connected.
+    pub fn from_grid<T: Eq + Debug>(grid: &[Vec<T>]) -> Result<Self, String> {
+        let size = grid.len();
+        if let Some(row) = grid.iter().position(|cells| cells.len() != size) {
+            return Err(format!(
+                "A layout of size {size} must have {size} cells per row, row {row} has {}.",
+                grid[row].len()
+            ));
+        }
+
+        let mut labels: Vec<&T> = Vec::with_capacity(size);
+        let mut boxes: Vec<Vec<(usize, usize)>> = Vec::with_capacity(size);
+        for (row, cells) in grid.iter().enumerate() {
+            for (col, label) in cells.iter().enumerate() {
+                if let Some(i) = labels.iter().position(|&known| known == label) {
+                    boxes[i].push((row, col));
+                } else {
+                    labels.push(label);
+                    boxes.push(vec![(row, col)]);
+                }
+            }
+        }
+
+        if boxes.len() != size {
+            return Err(format!(
+                "A layout of size {size} must have {size} boxes, found {}.",
+                boxes.len()
+            ));
+        }
+        let layout = Self::from_boxes(boxes);
+        for (label, box_) in labels.into_iter().zip(&layout.boxes) {
+            if box_.len() != size {
+                return Err(format!(
+                    "Box {label:?} has {} cells instead of {size}.",
+                    box_.len()
+                ));
+            }
+            if !layout.is_connected(box_) {
+                return Err(format!("Box {label:?} is not connected."));
+            }
+        }
+        Ok(layout)
+    }
+
+    fn is_connected(&self, box_: &[(usize, usize)]) -> bool {
+        let index = self.box_index[box_[0].0][box_[0].1];
+        let mut seen = vec![box_[0]];
+        let mut pending = vec![box_[0]];
+        while let Some((row, col)) = pending.pop() {
+            let neighbours = [
+                (row.wrapping_sub(1), col),
+                (row + 1, col),
+                (row, col.wrapping_sub(1)),
+                (row, col + 1),
+            ];
+            for (row, col) in neighbours {
+                if row < self.size
+                    && col < self.size
+                    && self.box_index[row][col] == index
+                    && !seen.contains(&(row, col))
+                {
+                    seen.push((row, col));
+                    pending.push((row, col));
+                }
+            }
+        }
+        seen.len() == box_.len()
+    }
+
+    /// Adds both main diagonals as regions, like in Sudoku-X.
+    pub fn with_diagonals(mut self) -> Self {
+        let size = self.size;
+        self.push_extra_region(RegionType::Diagonal, (0..size).map(|i| (i, i)).collect());
+        self.push_extra_region(
+            RegionType::Diagonal,
+            (0..size).map(|i| (i, size - 1 - i)).collect(),
+        );
+        self
+    }
+
+    /// Adds a region of any `size` cells that must hold every value once, like a Windoku window.
+    pub fn with_extra_region(mut self, region: Vec<(usize, usize)>) -> Result<Self, String> {
+        if region.len() != self.size {
+            return Err(format!(
+                "An extra region must have {} cells, found {}.",
+                self.size,
+                region.len()
+            ));
+        }
+        if let Some(&(row, col)) = region
+            .iter()
+            .find(|&&(row, col)| row >= self.size || col >= self.size)
+        {
+            return Err(format!(
+                "Invalid position ({row},{col}) in an extra region."
+            ));
+        }
+        if (1..region.len()).any(|i| region[..i].contains(&region[i])) {
+            return Err(format!("An extra region has repeated cells: {region:?}."));
+        }
+        self.push_extra_region(RegionType::Extra, region);
+        Ok(self)
+    }
+
+    /// Adds the disjoint groups: the cells at the same position within each box, which need
+    /// rectangular boxes.
+    pub fn with_disjoint_groups(mut self) -> Result<Self, String> {
+        let Some((box_rows, box_cols)) = self.box_shape else {
+            return Err("Disjoint groups need rectangular boxes.".to_string());
+        };
+        let boxes = get_all_boxes(box_rows, box_cols);
+        for position in 0..self.size {
+            let group = boxes.iter().map(|box_| box_[position]).collect();
+            self.push_extra_region(RegionType::DisjointGroup, group);
+        }
+        Ok(self)
+    }
+
+    /// Adds the Windoku windows: square boxes shifted by one cell, with one cell between them.
+    pub fn with_windows(self) -> Result<Self, String> {
+        let Some((box_size, _)) = self.box_shape.filter(|&(rows, cols)| rows == cols) else {
+            return Err("Windows need square boxes.".to_string());
+        };
+        let starts: Vec<_> = (1..self.size)
+            .step_by(box_size + 1)
+            .filter(|&start| start + box_size < self.size)
+            .collect();
+
+        let mut layout = self;
+        for &row in &starts {
+            for &col in &starts {
+                let window = (row..row + box_size)
+                    .flat_map(|row| (col..col + box_size).map(move |col| (row, col)))
+                    .collect();
+                layout.push_extra_region(RegionType::Window, window);
+            }
+        }
+        Ok(layout)
+    }
+
+    pub fn with_chess_constraint(mut self, constraint: ChessConstraint) -> Self {
+        if !self.chess_constraints.contains(&constraint) {
+            self.chess_constraints.push(constraint);
+            self.index();
+        }
+        self
+    }
+
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The `(box_rows, box_cols)` of rectangular boxes, `None` for a jigsaw.
+    pub const fn box_shape(&self) -> Option<(usize, usize)> {
+        self.box_shape
+    }
+
+    pub fn boxes(&self) -> &[Vec<(usize, usize)>] {
+        &self.boxes
+    }
+
+    pub fn box_index(&self, row: usize, col: usize) -> usize {
+        self.box_index[row][col]
+    }
+
+    /// The cells of the box containing `(row, col)`.
+    pub fn box_of(&self, row: usize, col: usize) -> &[(usize, usize)] {
+        &self.boxes[self.box_index[row][col]]
+    }
+
+    /// The regions added to the rows, columns and boxes.
+    pub fn extra_regions(&self) -> &[(RegionType, Vec<(usize, usize)>)] {
+        &self.extra_regions
+    }
+
+    pub fn chess_constraints(&self) -> &[ChessConstraint] {
+        &self.chess_constraints
+    }
+
+    /// Whether the board is a plain sudoku, with rectangular boxes and no variant rules.
+    pub fn is_classic(&self) -> bool {
+        self.box_shape.is_some()
+            && self.extra_regions.is_empty()
+            && self.chess_constraints.is_empty()
+    }
+
+    /// The cells a chess move away from `(row, col)`.
+    pub fn chess_peers(&self, row: usize, col: usize) -> &[(usize, usize)] {
+        &self.chess_peers[row][col]
+    }
+
+    fn find_chess_peers(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        let mut peers = Vec::new();
+        for constraint in &self.chess_constraints {
+            for &(row_move, col_move) in constraint.moves() {
+                let peer = (
+                    row.checked_add_signed(row_move),
+                    col.checked_add_signed(col_move),
+                );
+                if let (Some(row), Some(col)) = peer
+                    && row < self.size
+                    && col < self.size
+                    && !peers.contains(&(row, col))
+                {
+                    peers.push((row, col));
+                }
+            }
+        }
+        peers
+    }
+
+    /// Whether the cells can't hold the same value, sharing a region or being a chess move apart.
+    pub fn sees(&self, (row, col): (usize, usize), other: (usize, usize)) -> bool {
+        (row, col) != other
+            && (self.cell_regions[row][col]
+                .iter()
+                .any(|i| self.cell_regions[other.0][other.1].contains(i))
+                || self.chess_peers[row][col].contains(&other))
+    }
+
+    /// The cells `(row, col)` sees, once for each region or chess move they share.
+    pub fn peers(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.cell_regions[row][col]
+            .iter()
+            .flat_map(|&i| self.regions[i].1.iter().copied())
+            .chain(self.chess_peers[row][col].iter().copied())
+            .filter(move |&cell| cell != (row, col))
+    }
+
+    /// The rows, the columns, the boxes and the extra regions.
+    pub fn regions(&self) -> Rc<[Region]> {
+        Rc::clone(&self.regions)
+    }
+
+    fn build_regions(&self) -> Vec<Region> {
+        let mut regions = Vec::with_capacity(self.size * 3 + self.extra_regions.len());
+
+        for i in 0..self.size {
+            regions.push((RegionType::Row, (0..self.size).map(|j| (i, j)).collect()));
+            regions.push((RegionType::Col, (0..self.size).map(|j| (j, i)).collect()));
+        }
+
+        regions.extend(self.boxes.iter().cloned().map(|v| (RegionType::Box, v)));
+        regions.extend(self.extra_regions.iter().cloned());
+        regions
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::{get_all_boxes, ChessConstraint, RegionLayout, RegionType};
+    use crate::puzzle::{parse_extra_regions, parse_layout, to_layout_string};
+
+    #[test]
+    fn parses_jigsaw_layout() {
+        let layout = parse_layout("1122 1122 3344 3344").unwrap();
+        assert_eq!(layout.box_shape(), None);
+        assert_eq!(layout.box_of(1, 3), [(0, 2), (0, 3), (1, 2), (1, 3)]);
+
+        let layout = parse_layout("AAAB ACBB CCDB CDDD").unwrap();
+        assert_eq!(layout.boxes().len(), 4);
+        assert_eq!(layout.box_of(1, 1), [(1, 1), (2, 0), (2, 1), (3, 0)]);
+        assert_eq!(to_layout_string(&layout), "1112\n1322\n3342\n3444");
+        assert_eq!(parse_layout(&to_layout_string(&layout)), Ok(layout));
+    }
+
+    #[test]
+    fn gets_rectangular_boxes() {
+        let boxes = get_all_boxes(2, 3);
+        assert_eq!(boxes.len(), 6);
+        assert_eq!(boxes[0], [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]);
+        assert_eq!(boxes[1], [(0, 3), (0, 4), (0, 5), (1, 3), (1, 4), (1, 5)]);
+        assert_eq!(boxes[5], [(4, 3), (4, 4), (4, 5), (5, 3), (5, 4), (5, 5)]);
+        let mut cells: Vec<_> = boxes.into_iter().flatten().collect();
+        cells.sort();
+        cells.dedup();
+        assert_eq!(cells.len(), 36);
+    }
+
+    #[test]
+    fn diagonals_are_extra_regions() {
+        let layout = RegionLayout::for_size(9).with_diagonals();
+        let regions = layout.regions();
+        let diagonals: Vec<_> = regions
+            .iter()
+            .filter(|(region_type, _)| *region_type == RegionType::Diagonal)
+            .map(|(_, region)| region)
+            .collect();
+        assert_eq!(layout.regions().len(), 29);
+        assert_eq!(diagonals.len(), 2);
+        assert!(diagonals[0].contains(&(4, 4)) && diagonals[1].contains(&(4, 4)));
+        assert!(diagonals[1].contains(&(0, 8)) && diagonals[1].contains(&(8, 0)));
+    }
+
+    #[test]
+    fn windows_are_extra_regions() {
+        let layout = RegionLayout::for_size(9).with_windows().unwrap();
+        assert_eq!(layout.extra_regions().len(), 4);
+        assert_eq!(layout.extra_regions()[3].0, RegionType::Window);
+        assert_eq!(layout.extra_regions()[3].1[0], (5, 5));
+        assert_eq!(layout.extra_regions()[3].1[8], (7, 7));
+        assert_eq!(
+            RegionLayout::for_size(16)
+                .with_windows()
+                .unwrap()
+                .extra_regions()
+                .len(),
+            9
+        );
+        assert!(RegionLayout::for_size(6).with_windows().is_err());
+    }
+
+    #[test]
+    fn disjoint_groups_are_extra_regions() {
+        let layout = RegionLayout::for_size(6).with_disjoint_groups().unwrap();
+        assert_eq!(layout.extra_regions().len(), 6);
+        let (region_type, group) = &layout.extra_regions()[4];
+        assert_eq!(*region_type, RegionType::DisjointGroup);
+        assert_eq!(group, &[(1, 1), (1, 4), (3, 1), (3, 4), (5, 1), (5, 4)]);
+        assert!(layout.sees((0, 0), (2, 3)) && !layout.sees((0, 0), (2, 4)));
+        assert!(parse_layout("1122 1122 3344 3344")
+            .unwrap()
+            .with_disjoint_groups()
+            .is_err());
+    }
+
+    #[test]
+    fn parses_extra_regions() {
+        let layout = parse_extra_regions("A..A .... .... A..A", RegionLayout::for_size(4)).unwrap();
+        assert_eq!(layout.extra_regions().len(), 1);
+        assert_eq!(
+            layout.extra_regions()[0].1,
+            [(0, 0), (0, 3), (3, 0), (3, 3)]
+        );
+
+        // too few cells and repeated cells
+        assert!(parse_extra_regions("A..A .... .... A...", RegionLayout::for_size(4)).is_err());
+        assert!(RegionLayout::for_size(4)
+            .with_extra_region(vec![(0, 0), (0, 0), (1, 1), (2, 2)])
+            .is_err());
+    }
+
+    #[test]
+    fn chess_constraints_add_peers() {
+        let knight = RegionLayout::for_size(9).with_chess_constraint(ChessConstraint::AntiKnight);
+        assert_eq!(knight.chess_peers(0, 0), [(1, 2), (2, 1)]);
+        assert_eq!(knight.chess_peers(4, 4).len(), 8);
+        assert!(knight.sees((2, 2), (3, 4)) && !knight.sees((2, 2), (3, 3)));
+
+        let both = knight.with_chess_constraint(ChessConstraint::AntiKing);
+        assert_eq!(both.chess_peers(4, 4).len(), 16);
+        assert!(both.sees((2, 2), (3, 3)));
+        assert!(!both.is_classic());
+    }
+
+    #[test]
+    fn rejects_invalid_layouts() {
+        // boxes of the wrong size
+        assert!(parse_layout("1111 1222 3344 3344").is_err());
+        // too many boxes
+        assert!(parse_layout("1122 1122 3344 3345").is_err());
+        // disconnected box
+        assert!(parse_layout("1221 1221 3344 3344").is_err());
+        // not a square
+        assert!(parse_layout("112 1122 3344 3344").is_err());
+    }
 }
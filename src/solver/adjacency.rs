@@ -0,0 +1,248 @@
+use crate::board::SudokuBoard;
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::solver::SudokuRuleEnforcer;
+
+type Cell = (usize, usize);
+
+/// How the values of two orthogonally adjacent cells relate.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Relation {
+    /// Kropki white dot.
+    Consecutive,
+    /// Kropki black dot, one value is twice the other.
+    Double,
+    NotConsecutive,
+    /// No Kropki dot under the negative constraint.
+    NeitherConsecutiveNorDouble,
+    /// The value of the first cell is greater than the one of the second.
+    Greater,
+    Less,
+}
+
+impl Relation {
+    pub const fn holds(self, a: usize, b: usize) -> bool {
+        let consecutive = a.abs_diff(b) == 1;
+        let double = a == 2 * b || b == 2 * a;
+        match self {
+            Relation::Consecutive => consecutive,
+            Relation::Double => double,
+            Relation::NotConsecutive => !consecutive,
+            Relation::NeitherConsecutiveNorDouble => !consecutive && !double,
+            Relation::Greater => a > b,
+            Relation::Less => a < b,
+        }
+    }
+
+    /// The relation with the cells swapped.
+    const fn flipped(self) -> Self {
+        match self {
+            Relation::Greater => Relation::Less,
+            Relation::Less => Relation::Greater,
+            relation => relation,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Adjacency {
+    pub cells: (Cell, Cell),
+    pub relation: Relation,
+}
+
+impl Adjacency {
+    pub fn new(first: Cell, second: Cell, relation: Relation) -> Result<Self, String> {
+        if first.0.abs_diff(second.0) + first.1.abs_diff(second.1) != 1 {
+            return Err(format!(
+                "The cells {first:?} and {second:?} are not orthogonally adjacent."
+            ));
+        }
+        Ok(if first < second {
+            Self {
+                cells: (first, second),
+                relation,
+            }
+        } else {
+            Self {
+                cells: (second, first),
+                relation: relation.flipped(),
+            }
+        })
+    }
+}
+
+/// Every pair of orthogonally adjacent cells.
+fn orthogonal_pairs(size: usize) -> impl Iterator<Item = (Cell, Cell)> {
+    (0..size).flat_map(move |row| {
+        (0..size).flat_map(move |col| {
+            let right = (col + 1 < size).then_some(((row, col), (row, col + 1)));
+            let below = (row + 1 < size).then_some(((row, col), (row + 1, col)));
+            right.into_iter().chain(below)
+        })
+    })
+}
+
+/// The non-consecutive variant: no orthogonally adjacent cells hold consecutive values.
+pub fn non_consecutive(size: usize) -> Vec<Adjacency> {
+    orthogonal_pairs(size)
+        .map(|cells| Adjacency {
+            cells,
+            relation: Relation::NotConsecutive,
+        })
+        .collect()
+}
+
+/// Kropki dots, with the negative constraint every adjacent pair without a dot is neither
+/// consecutive nor double.
+pub fn kropki(dots: Vec<Adjacency>, negative: bool, size: usize) -> Vec<Adjacency> {
+    let mut adjacencies = dots;
+    if negative {
+        let undotted: Vec<_> = orthogonal_pairs(size)
+            .filter(|&cells| !adjacencies.iter().any(|dot| dot.cells == cells))
+            .map(|cells| Adjacency {
+                cells,
+                relation: Relation::NeitherConsecutiveNorDouble,
+            })
+            .collect();
+        adjacencies.extend(undotted);
+    }
+    adjacencies
+}
+
+/// Keeps the candidates of each cell having a matching candidate in its adjacent cell.
+#[derive(Clone)]
+pub struct AdjacencyEnforcer<const N: usize> {
+    adjacencies: Vec<Adjacency>,
+}
+
+impl<const N: usize> AdjacencyEnforcer<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    pub const fn new(adjacencies: Vec<Adjacency>) -> Self {
+        Self { adjacencies }
+    }
+
+    /// The values of `cell` matching some value of `other`, adjacent cells always seeing
+    /// each other.
+    fn supported_values(
+        board: &SudokuBoard<N>,
+        cell: Cell,
+        other: Cell,
+        holds: impl Fn(usize, usize) -> bool,
+    ) -> Vec<usize> {
+        board
+            .get_possible_values(cell.0, cell.1)
+            .filter(|&value| {
+                board
+                    .get_possible_values(other.0, other.1)
+                    .any(|other_value| other_value != value && holds(value, other_value))
+            })
+            .collect()
+    }
+}
+
+impl<const N: usize> SudokuRuleEnforcer<N> for AdjacencyEnforcer<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    fn name(&self) -> &'static str {
+        "AdjacencyEnforcer"
+    }
+
+    fn enforce_rule(&mut self, board: &mut SudokuBoard<N>) -> Result<bool, String> {
+        for &Adjacency {
+            cells: (first, second),
+            relation,
+        } in &self.adjacencies
+        {
+            let values = Self::supported_values(board, first, second, |a, b| relation.holds(a, b));
+            let other_values =
+                Self::supported_values(board, second, first, |b, a| relation.holds(a, b));
+            for ((row, col), values) in [(first, values), (second, other_values)] {
+                if values.is_empty() {
+                    return Err(format!(
+                        "Invalid Board, no value of ({row},{col}) is {relation:?} to its neighbour."
+                    ));
+                }
+                let is_solved = board.retain_values(row, col, &values)?;
+                if is_solved {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{kropki, non_consecutive, Adjacency, AdjacencyEnforcer, Relation};
+    use crate::board::SudokuBoard;
+    use crate::solver::SudokuRuleEnforcer;
+
+    #[test]
+    fn relations() {
+        assert!(Relation::Consecutive.holds(4, 5) && !Relation::Consecutive.holds(4, 6));
+        assert!(Relation::Double.holds(3, 6) && Relation::Double.holds(8, 4));
+        assert!(Relation::NeitherConsecutiveNorDouble.holds(3, 7));
+        assert!(!Relation::NeitherConsecutiveNorDouble.holds(1, 2));
+    }
+
+    #[test]
+    fn counts_adjacent_pairs() {
+        assert_eq!(non_consecutive(9).len(), 2 * 9 * 8);
+        let dot = Adjacency::new((0, 1), (0, 0), Relation::Double).unwrap();
+        assert_eq!(dot.cells, ((0, 0), (0, 1)));
+        assert_eq!(kropki(vec![dot], true, 9).len(), 2 * 9 * 8);
+        assert!(Adjacency::new((0, 0), (1, 1), Relation::Double).is_err());
+        let sign = Adjacency::new((1, 0), (0, 0), Relation::Greater).unwrap();
+        assert_eq!(sign.cells, ((0, 0), (1, 0)));
+        assert_eq!(sign.relation, Relation::Less);
+    }
+
+    #[test]
+    fn prunes_dotted_cells() {
+        let mut board = SudokuBoard::<9>::new();
+        board.set(0, 0, 3).unwrap();
+        let dots = vec![
+            Adjacency::new((0, 0), (0, 1), Relation::Double).unwrap(),
+            Adjacency::new((1, 0), (1, 1), Relation::Consecutive).unwrap(),
+        ];
+        board.set(1, 1, 9).unwrap();
+        AdjacencyEnforcer::new(dots)
+            .enforce_rule(&mut board)
+            .unwrap();
+        assert_eq!(board.get_value(0, 1), Some(6));
+        assert_eq!(board.get_value(1, 0), Some(8));
+    }
+
+    #[test]
+    fn removes_consecutive_neighbours() {
+        let mut board = SudokuBoard::<9>::new();
+        board.set(4, 4, 5).unwrap();
+        AdjacencyEnforcer::new(non_consecutive(9))
+            .enforce_rule(&mut board)
+            .unwrap();
+        for (row, col) in [(3, 4), (5, 4), (4, 3), (4, 5)] {
+            let values: Vec<_> = board.get_possible_values(row, col).collect();
+            assert_eq!(values, [1, 2, 3, 7, 8, 9]);
+        }
+        assert_eq!(board.get_possible_values(3, 3).count(), 8);
+    }
+
+    #[test]
+    fn prunes_inequalities() {
+        // a chain of three cells increasing from the left.
+        let signs = vec![
+            Adjacency::new((0, 1), (0, 0), Relation::Greater).unwrap(),
+            Adjacency::new((0, 1), (0, 2), Relation::Less).unwrap(),
+        ];
+        let mut board = SudokuBoard::<9>::new();
+        board.set(0, 1, 2).unwrap();
+        AdjacencyEnforcer::new(signs)
+            .enforce_rule(&mut board)
+            .unwrap();
+        assert_eq!(board.get_value(0, 0), Some(1));
+        assert_eq!(board.get_possible_values(0, 2).count(), 7);
+    }
+}
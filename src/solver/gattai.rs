@@ -0,0 +1,378 @@
+//! Gattai puzzles keep a board per grid, the cells covered by several grids being copies in each
+//! of them rather than shared storage. `GattaiBoard::set` and every pass of the rules over a
+//! grid end by reconciling the copies with `share_candidates`, so they always agree once a change
+//! or a pass returns.
+
+use crate::board::SudokuBoard;
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::solver::{rule_enforcers, SudokuRuleEnforcer};
+use std::fmt::{Display, Formatter};
+
+/// Where the grids of a gattai puzzle, like a Samurai, sit on the composite grid: each grid is
+/// `size` by `size` cells from its top left corner, and cells covered by several grids are shared.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GattaiLayout {
+    size: usize,
+    corners: Vec<(usize, usize)>,
+    shared: Vec<Vec<(usize, usize, usize)>>,
+}
+
+impl GattaiLayout {
+    pub fn new(size: usize, corners: Vec<(usize, usize)>) -> Result<Self, String> {
+        if corners.is_empty() {
+            return Err("A gattai layout needs at least one grid.".to_string());
+        }
+        if (1..corners.len()).any(|i| corners[..i].contains(&corners[i])) {
+            return Err(format!("A gattai layout has repeated grids: {corners:?}."));
+        }
+        let mut layout = Self {
+            size,
+            corners,
+            shared: Vec::new(),
+        };
+        layout.shared = layout.find_shared_cells();
+        Ok(layout)
+    }
+
+    /// Five grids, one in each corner and one in the middle sharing a corner box with each.
+    pub fn samurai(size: usize) -> Result<Self, String> {
+        let box_size = size.isqrt();
+        if box_size * box_size != size {
+            return Err(format!(
+                "A samurai needs square boxes, {size}x{size} grids have none."
+            ));
+        }
+        let far = size + box_size;
+        let middle = size - box_size;
+        Self::new(
+            size,
+            vec![(0, 0), (0, far), (middle, middle), (far, 0), (far, far)],
+        )
+    }
+
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn corners(&self) -> &[(usize, usize)] {
+        &self.corners
+    }
+
+    /// The number of rows and columns of the composite grid.
+    pub fn dimensions(&self) -> (usize, usize) {
+        let rows = self.corners.iter().map(|corner| corner.0).max().unwrap();
+        let cols = self.corners.iter().map(|corner| corner.1).max().unwrap();
+        (rows + self.size, cols + self.size)
+    }
+
+    /// The grids covering a cell of the composite grid, with the position of the cell in each.
+    pub fn grids_at(&self, row: usize, col: usize) -> Vec<(usize, usize, usize)> {
+        self.corners
+            .iter()
+            .enumerate()
+            .filter(|&(_, &(top, left))| {
+                (top..top + self.size).contains(&row) && (left..left + self.size).contains(&col)
+            })
+            .map(|(grid, &(top, left))| (grid, row - top, col - left))
+            .collect()
+    }
+
+    /// The cells covered by more than one grid.
+    fn shared_cells(&self) -> &[Vec<(usize, usize, usize)>] {
+        &self.shared
+    }
+
+    fn find_shared_cells(&self) -> Vec<Vec<(usize, usize, usize)>> {
+        let (rows, cols) = self.dimensions();
+        (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (row, col)))
+            .map(|(row, col)| self.grids_at(row, col))
+            .filter(|grids| grids.len() > 1)
+            .collect()
+    }
+}
+
+/// The boards of the grids of a gattai puzzle, kept in agreement on their shared cells.
+#[derive(Clone)]
+pub struct GattaiBoard<const N: usize>
+where
+    ForSize<N>: StorageForSize,
+{
+    layout: GattaiLayout,
+    grids: Vec<SudokuBoard<N>>,
+}
+
+impl<const N: usize> GattaiBoard<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    pub fn new(layout: GattaiLayout) -> Self {
+        assert!(
+            layout.size() == N,
+            "Invalid gattai layout of size {} for size {N}",
+            layout.size()
+        );
+        let grids = vec![SudokuBoard::new(); layout.corners().len()];
+        Self { layout, grids }
+    }
+
+    /// Sets a cell of the composite grid in every grid covering it, sharing the eliminations it
+    /// causes with the other grids.
+    pub fn set(&mut self, row: usize, col: usize, value: usize) -> Result<(), String> {
+        let grids = self.layout.grids_at(row, col);
+        if grids.is_empty() {
+            return Err(format!("The cell ({row},{col}) isn't in any grid."));
+        }
+        for (grid, grid_row, grid_col) in grids {
+            if self.grids[grid].get_value(grid_row, grid_col) != Some(value) {
+                self.grids[grid].set(grid_row, grid_col, value)?;
+            }
+        }
+        self.share_candidates()
+    }
+
+    pub fn get_value(&self, row: usize, col: usize) -> Option<usize> {
+        let &(grid, grid_row, grid_col) = self.layout.grids_at(row, col).first()?;
+        self.grids[grid].get_value(grid_row, grid_col)
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.grids.iter().all(SudokuBoard::is_solved)
+    }
+
+    /// Keeps only the candidates every grid allows on each shared cell, until they agree.
+    fn share_candidates(&mut self) -> Result<(), String> {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for cells in self.layout.shared_cells() {
+                let mut values: Vec<usize> = (1..=N).collect();
+                for &(grid, row, col) in cells {
+                    let candidates: Vec<_> =
+                        self.grids[grid].get_possible_values(row, col).collect();
+                    values.retain(|value| candidates.contains(value));
+                }
+                if values.is_empty() {
+                    return Err(format!(
+                        "Invalid Board, the grids sharing {cells:?} have no common value."
+                    ));
+                }
+                for &(grid, row, col) in cells {
+                    if self.grids[grid].get_possible_values(row, col).count() > values.len() {
+                        self.grids[grid].retain_values(row, col, &values)?;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> Display for GattaiBoard<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (rows, cols) = self.layout.dimensions();
+        for row in 0..rows {
+            let line: String = (0..cols)
+                .map(|col| match self.layout.grids_at(row, col).first() {
+                    None => " ".to_string(),
+                    Some(_) => self
+                        .get_value(row, col)
+                        .map_or(".".to_string(), |value| value.to_string()),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Solves gattai puzzles, applying the rules to each grid and sharing what they find on the
+/// common cells with the other grids.
+pub struct GattaiSolver<const N: usize>
+where
+    ForSize<N>: StorageForSize,
+{
+    board: GattaiBoard<N>,
+    enforcers: Vec<Vec<Box<dyn SudokuRuleEnforcer<N>>>>,
+    search: bool,
+}
+
+impl<const N: usize> GattaiSolver<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    pub fn new(layout: GattaiLayout) -> Self {
+        let enforcers = layout.corners().iter().map(|_| rule_enforcers()).collect();
+        Self {
+            board: GattaiBoard::new(layout),
+            enforcers,
+            search: false,
+        }
+    }
+
+    /// Guesses values once the rules can't make any progress, backtracking on contradictions.
+    pub const fn with_search(mut self) -> Self {
+        self.search = true;
+        self
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: usize) -> Result<(), String> {
+        self.board.set(row, col, value)
+    }
+
+    pub fn solve(mut self) -> Result<GattaiBoard<N>, String> {
+        self.propagate()?;
+        if self.search && !self.board.is_solved() {
+            self.search()?;
+        }
+        Ok(self.board)
+    }
+
+    /// Applies the rules to every grid until none of them improves the board.
+    fn propagate(&mut self) -> Result<(), String> {
+        loop {
+            for grid in &mut self.board.grids {
+                grid.improved.clear();
+            }
+            for (grid, enforcers) in self.enforcers.iter_mut().enumerate() {
+                for rule_enforcer in enforcers {
+                    if rule_enforcer.enforce_rule(&mut self.board.grids[grid])? {
+                        break;
+                    }
+                }
+                self.board.share_candidates()?;
+            }
+            if self.board.grids.iter().all(|grid| grid.improved.is_empty()) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Tries every value of the cell with the fewest candidates, like `SudokuSolver::search`.
+    fn search(&mut self) -> Result<(), String> {
+        let Some((grid, row, col)) = (0..self.board.grids.len())
+            .flat_map(|grid| (0..N).flat_map(move |row| (0..N).map(move |col| (grid, row, col))))
+            .filter(|&(grid, row, col)| self.board.grids[grid].get_value(row, col).is_none())
+            .min_by_key(|&(grid, row, col)| {
+                self.board.grids[grid].get_possible_values(row, col).count()
+            })
+        else {
+            return Ok(());
+        };
+
+        let (top, left) = self.board.layout.corners()[grid];
+        let values: Vec<_> = self.board.grids[grid]
+            .get_possible_values(row, col)
+            .collect();
+        for value in values {
+            let saved = (self.board.clone(), self.enforcers.clone());
+            let guess = self
+                .board
+                .set(top + row, left + col, value)
+                .and_then(|()| self.propagate())
+                .and_then(|()| self.search());
+            if guess.is_ok() && self.board.is_solved() {
+                return Ok(());
+            }
+            (self.board, self.enforcers) = saved;
+        }
+        Err(format!(
+            "Invalid Board, no value fits at ({},{}).",
+            top + row,
+            left + col
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GattaiBoard, GattaiLayout, GattaiSolver};
+    use crate::dlx::dlx_solver::DlxSolver;
+    use crate::puzzle::{parse_gattai, parse_puzzle};
+
+    const SAMURAI_PUZZLE: &str = "
+        ......3.9...2......89...1...61..2.8..5....4.......3..75........62.19.85...86.....
+        9..6...541.2..8.......4........65.83.3.....4...9.3............2...9....7...3.6...
+        .....8...85.1.........5...........5....9...72.6....4......2........9.........3...
+        ..4..9.....9.......7..3.....8.7.3.6.....6..28......15.....14...8..9......16..8...
+        .............7..48....5.1...56....7.4....16........3..2..83.......2....17.5......";
+
+    const SAMURAI_SOLUTION: &str = "
+        245718369136249578789356124361472985857961432492583617513824796624197853978635241
+        987613254142578369653249178421765983536892741879134526315487692264951837798326415
+        796248315853179264241356798127834956438965172569712483375621849682497531914583627
+        124689375359147682678235914281753469593461728467892153732514896845976231916328547
+        849123567531679248627458139156382974473591682982746315214835796398267451765914823";
+
+    #[test]
+    fn samurai_layout() {
+        let layout = GattaiLayout::samurai(9).unwrap();
+        assert_eq!(layout.dimensions(), (21, 21));
+        assert_eq!(layout.grids_at(7, 7), [(0, 7, 7), (2, 1, 1)]);
+        assert_eq!(layout.grids_at(10, 10), [(2, 4, 4)]);
+        assert!(layout.grids_at(10, 0).is_empty());
+        assert_eq!(layout.shared_cells().len(), 4 * 9);
+        assert!(GattaiLayout::samurai(6).is_err());
+        assert!(GattaiLayout::new(9, vec![(0, 0), (0, 0)]).is_err());
+    }
+
+    #[test]
+    fn shared_cells_propagate() {
+        let mut board = GattaiBoard::<9>::new(GattaiLayout::samurai(9).unwrap());
+        // (6,8) is shared by the top left grid and the middle one.
+        board.set(6, 8, 5).unwrap();
+        assert_eq!(board.grids[0].get_value(6, 8), Some(5));
+        assert_eq!(board.grids[2].get_value(0, 2), Some(5));
+        // the middle grid removes the 5 from its row, outside of the top left grid.
+        assert!(!board.grids[2]
+            .get_possible_values(0, 5)
+            .any(|value| value == 5));
+        assert!(board.set(6, 14, 5).is_err());
+        assert!(board.set(10, 0, 5).is_err());
+    }
+
+    #[test]
+    fn eliminations_reach_the_overlapping_grid() {
+        let mut board = GattaiBoard::<9>::new(GattaiLayout::samurai(9).unwrap());
+        // (6,0) is only in the top left grid, its row crosses the box shared with the middle grid.
+        board.set(6, 0, 5).unwrap();
+        assert!(board.grids[2].get_value(0, 0).is_none());
+        for col in 0..3 {
+            assert!(!board.grids[2]
+                .get_possible_values(0, col)
+                .any(|value| value == 5));
+        }
+        // the middle grid can still place a 5 in its first row, outside of the shared box.
+        assert!(board.grids[2]
+            .get_possible_values(0, 3)
+            .any(|value| value == 5));
+    }
+
+    #[test]
+    fn solves_samurai() {
+        let layout = GattaiLayout::samurai(9).unwrap();
+        let mut solver = GattaiSolver::<9>::new(layout.clone());
+        for (row, col, value) in parse_gattai(SAMURAI_PUZZLE, &layout).unwrap() {
+            solver.set(row, col, value).unwrap();
+        }
+        let solved = solver.solve().unwrap();
+        assert!(solved.is_solved());
+        for (row, col, value) in parse_gattai(SAMURAI_SOLUTION, &layout).unwrap() {
+            assert_eq!(solved.get_value(row, col), Some(value));
+        }
+
+        // the givens of the middle grid alone don't make a proper puzzle.
+        let middle = SAMURAI_PUZZLE.lines().map(str::trim).nth(3).unwrap();
+        let (_, givens) = parse_puzzle(middle).unwrap();
+        let mut dlx_solver = DlxSolver::<9>::new();
+        for (row, col, value) in givens {
+            dlx_solver.set(row, col, value);
+        }
+        assert!(!dlx_solver.has_unique_solution());
+    }
+}
@@ -0,0 +1,58 @@
+use crate::board::SudokuBoard;
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::solver::SudokuRuleEnforcer;
+
+/// Sets a value whenever it has only one possible position left in a region.
+#[derive(Clone)]
+pub struct HiddenSingleEnforcer<const N: usize>;
+
+impl<const N: usize> HiddenSingleEnforcer<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl<const N: usize> SudokuRuleEnforcer<N> for HiddenSingleEnforcer<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    fn name(&self) -> &'static str {
+        "HiddenSingleEnforcer"
+    }
+
+    fn enforce_rule(&mut self, board: &mut SudokuBoard<N>) -> Result<bool, String> {
+        let regions = board.regions();
+
+        for (_, region) in regions.iter() {
+            let mut positions = vec![Vec::new(); board.size()];
+            for &(row, col) in region {
+                for value in board.get_possible_values(row, col) {
+                    positions[value - 1].push((row, col));
+                }
+            }
+
+            for (i, value_positions) in positions.into_iter().enumerate() {
+                let value = i + 1;
+                match value_positions[..] {
+                    [] => {
+                        return Err(format!(
+                            "Invalid Board, no possible position for {value} in {region:?}."
+                        ))
+                    }
+                    [(row, col)] if board.get_value(row, col).is_none() => {
+                        let is_solved = board.set(row, col, value)?;
+                        if is_solved {
+                            return Ok(true);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(false)
+    }
+}
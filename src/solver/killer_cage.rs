@@ -0,0 +1,313 @@
+use crate::board::SudokuBoard;
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::solver::SudokuRuleEnforcer;
+use std::collections::{HashMap, HashSet};
+
+/// Killer sudoku cage: its cells hold different values adding up to `sum`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cage {
+    pub sum: usize,
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl Cage {
+    pub fn new(sum: usize, cells: Vec<(usize, usize)>, size: usize) -> Result<Self, String> {
+        if cells.is_empty() || cells.len() > size {
+            return Err(format!(
+                "A cage must have between 1 and {size} cells, found {}.",
+                cells.len()
+            ));
+        }
+        if let Some(&(row, col)) = cells.iter().find(|&&(row, col)| row >= size || col >= size) {
+            return Err(format!("Invalid position ({row},{col}) in a cage."));
+        }
+        if (1..cells.len()).any(|i| cells[..i].contains(&cells[i])) {
+            return Err(format!("A cage has repeated cells: {cells:?}."));
+        }
+
+        let min_sum: usize = (1..=cells.len()).sum();
+        let max_sum: usize = (size + 1 - cells.len()..=size).sum();
+        if sum < min_sum || sum > max_sum {
+            return Err(format!(
+                "A cage of {} cells can't add up to {sum}, expected between {min_sum} and {max_sum}.",
+                cells.len()
+            ));
+        }
+        Ok(Self { sum, cells })
+    }
+}
+
+/// Cells whose values add up to a known sum.
+///
+/// Cages and the cells the 45 rule finds inside a region hold different values, the cells
+/// it finds outside of a region may repeat them.
+struct SumGroup {
+    sum: usize,
+    cells: Vec<(usize, usize)>,
+    distinct: bool,
+}
+
+/// Prunes the candidates of the cages to the values of the combinations adding up to their sum,
+/// and of the innies and outies of every region (the 45 rule): the cells of a region outside of
+/// the cages it contains add up to the rest of its sum, and so do the cells sticking out of a
+/// region entirely covered by cages.
+#[derive(Clone)]
+pub struct KillerCageEnforcer<const N: usize> {
+    cages: Vec<Cage>,
+}
+
+impl<const N: usize> KillerCageEnforcer<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    pub const fn new(cages: Vec<Cage>) -> Self {
+        Self { cages }
+    }
+
+    fn sum_groups(&self, board: &SudokuBoard<N>) -> Vec<SumGroup> {
+        let region_sum: usize = (1..=N).sum();
+        let mut groups: Vec<_> = self
+            .cages
+            .iter()
+            .map(|cage| SumGroup {
+                sum: cage.sum,
+                cells: cage.cells.clone(),
+                distinct: true,
+            })
+            .collect();
+
+        for (_, region) in board.regions().iter() {
+            let (inside, overlapping): (Vec<&Cage>, Vec<&Cage>) = self
+                .cages
+                .iter()
+                .filter(|cage| cage.cells.iter().any(|cell| region.contains(cell)))
+                .partition(|cage| cage.cells.iter().all(|cell| region.contains(cell)));
+
+            let innies: Vec<_> = region
+                .iter()
+                .copied()
+                .filter(|cell| !inside.iter().any(|cage| cage.cells.contains(cell)))
+                .collect();
+            let inside_sum: usize = inside.iter().map(|cage| cage.sum).sum();
+            if !inside.is_empty() && !innies.is_empty() && inside_sum <= region_sum {
+                groups.push(SumGroup {
+                    sum: region_sum - inside_sum,
+                    cells: innies,
+                    distinct: true,
+                });
+            }
+
+            let covered = is_covered(region, &inside, &overlapping);
+            let outies: Vec<_> = overlapping
+                .iter()
+                .flat_map(|cage| cage.cells.iter().copied())
+                .filter(|cell| !region.contains(cell))
+                .collect();
+            let overlapping_sum: usize =
+                overlapping.iter().map(|cage| cage.sum).sum::<usize>() + inside_sum;
+            if covered && !outies.is_empty() && overlapping_sum >= region_sum {
+                groups.push(SumGroup {
+                    sum: overlapping_sum - region_sum,
+                    cells: outies,
+                    distinct: false,
+                });
+            }
+        }
+        groups
+    }
+
+    /// The values every cell of the group can take in some combination adding up to its sum.
+    fn feasible_values(board: &SudokuBoard<N>, group: &SumGroup) -> Vec<Vec<usize>> {
+        let candidates: Vec<Vec<usize>> = group
+            .cells
+            .iter()
+            .map(|&(row, col)| board.get_possible_values(row, col).collect())
+            .collect();
+        if group.distinct {
+            Self::feasible_distinct_values(&candidates, group.sum)
+        } else {
+            Self::feasible_repeated_values(&candidates, group.sum)
+        }
+    }
+
+    /// Goes over the sets of values used by the first cells (as bit masks), then back from the
+    /// ones adding up to `sum` to keep the values leading to them.
+    pub fn feasible_distinct_values(candidates: &[Vec<usize>], sum: usize) -> Vec<Vec<usize>> {
+        let mut layers: Vec<HashMap<u128, usize>> = vec![HashMap::from([(0, 0)])];
+        for cell_candidates in candidates {
+            let mut next = HashMap::new();
+            for (&used, &used_sum) in layers.last().unwrap() {
+                for &value in cell_candidates {
+                    let bit = 1 << (value - 1);
+                    if used & bit == 0 && used_sum + value <= sum {
+                        next.insert(used | bit, used_sum + value);
+                    }
+                }
+            }
+            layers.push(next);
+        }
+
+        let mut feasible = vec![Vec::new(); candidates.len()];
+        let mut reachable: HashSet<u128> = layers
+            .pop()
+            .unwrap()
+            .into_iter()
+            .filter(|&(_, used_sum)| used_sum == sum)
+            .map(|(used, _)| used)
+            .collect();
+        for (i, layer) in layers.iter().enumerate().rev() {
+            let mut previous = HashSet::new();
+            for &used in layer.keys() {
+                for &value in &candidates[i] {
+                    let bit = 1 << (value - 1);
+                    if used & bit == 0 && reachable.contains(&(used | bit)) {
+                        if !feasible[i].contains(&value) {
+                            feasible[i].push(value);
+                        }
+                        previous.insert(used);
+                    }
+                }
+            }
+            reachable = previous;
+        }
+        feasible
+    }
+
+    /// Like `feasible_distinct_values`, going over the sums of the first cells.
+    pub fn feasible_repeated_values(candidates: &[Vec<usize>], sum: usize) -> Vec<Vec<usize>> {
+        let mut layers = vec![vec![false; sum + 1]];
+        layers[0][0] = true;
+        for cell_candidates in candidates {
+            let mut next = vec![false; sum + 1];
+            for partial in (0..=sum).filter(|&partial| layers.last().unwrap()[partial]) {
+                for &value in cell_candidates
+                    .iter()
+                    .filter(|&&value| partial + value <= sum)
+                {
+                    next[partial + value] = true;
+                }
+            }
+            layers.push(next);
+        }
+
+        let mut feasible = vec![Vec::new(); candidates.len()];
+        let mut reachable = vec![false; sum + 1];
+        reachable[sum] = layers.pop().unwrap()[sum];
+        for (i, layer) in layers.iter().enumerate().rev() {
+            let mut previous = vec![false; sum + 1];
+            for partial in (0..=sum).filter(|&partial| layer[partial]) {
+                for &value in &candidates[i] {
+                    if partial + value <= sum && reachable[partial + value] {
+                        if !feasible[i].contains(&value) {
+                            feasible[i].push(value);
+                        }
+                        previous[partial] = true;
+                    }
+                }
+            }
+            reachable = previous;
+        }
+        feasible
+    }
+}
+
+/// Whether every cell of the region is in one of the cages.
+fn is_covered(region: &[(usize, usize)], inside: &[&Cage], overlapping: &[&Cage]) -> bool {
+    region.iter().all(|cell| {
+        inside
+            .iter()
+            .chain(overlapping)
+            .any(|cage| cage.cells.contains(cell))
+    })
+}
+
+impl<const N: usize> SudokuRuleEnforcer<N> for KillerCageEnforcer<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    fn name(&self) -> &'static str {
+        "KillerCageEnforcer"
+    }
+
+    fn enforce_rule(&mut self, board: &mut SudokuBoard<N>) -> Result<bool, String> {
+        for group in self.sum_groups(board) {
+            let feasible = Self::feasible_values(board, &group);
+            for (&(row, col), values) in group.cells.iter().zip(feasible) {
+                if values.is_empty() {
+                    return Err(format!(
+                        "Invalid Board, no values of ({row},{col}) add up to {} in {:?}.",
+                        group.sum, group.cells
+                    ));
+                }
+                let is_solved = board.retain_values(row, col, &values)?;
+                if is_solved {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cage, KillerCageEnforcer};
+    use crate::board::SudokuBoard;
+    use crate::solver::SudokuRuleEnforcer;
+
+    fn candidates(board: &SudokuBoard<9>, row: usize, col: usize) -> Vec<usize> {
+        board.get_possible_values(row, col).collect()
+    }
+
+    #[test]
+    fn prunes_to_cage_combinations() {
+        let cages = vec![
+            Cage::new(3, vec![(0, 0), (0, 1)], 9).unwrap(),
+            Cage::new(24, vec![(1, 0), (1, 1), (1, 2)], 9).unwrap(),
+        ];
+        let mut board = SudokuBoard::<9>::new();
+        KillerCageEnforcer::new(cages)
+            .enforce_rule(&mut board)
+            .unwrap();
+
+        assert_eq!(candidates(&board, 0, 0), [1, 2]);
+        assert_eq!(candidates(&board, 0, 1), [1, 2]);
+        assert_eq!(candidates(&board, 1, 2), [7, 8, 9]);
+        assert_eq!(candidates(&board, 2, 2).len(), 9);
+    }
+
+    #[test]
+    fn applies_the_45_rule() {
+        // the first row minus a cage of 8 cells adding up to 40 leaves 5 in its last cell.
+        let cells = (0..8).map(|col| (0, col)).collect();
+        let mut board = SudokuBoard::<9>::new();
+        KillerCageEnforcer::new(vec![Cage::new(40, cells, 9).unwrap()])
+            .enforce_rule(&mut board)
+            .unwrap();
+        assert_eq!(board.get_value(0, 8), Some(5));
+    }
+
+    #[test]
+    fn applies_the_45_rule_to_outies() {
+        // the cages covering the first row add up to 50, 5 more than the row: r2c1 sticks out.
+        let mut cells: Vec<_> = (0..8).map(|col| (0, col)).collect();
+        cells.push((1, 0));
+        let cages = vec![
+            Cage::new(45, cells, 9).unwrap(),
+            Cage::new(5, vec![(0, 8)], 9).unwrap(),
+        ];
+        let mut board = SudokuBoard::<9>::new();
+        KillerCageEnforcer::new(cages)
+            .enforce_rule(&mut board)
+            .unwrap();
+        assert_eq!(board.get_value(1, 0), Some(5));
+    }
+
+    #[test]
+    fn rejects_invalid_cages() {
+        assert!(Cage::new(2, vec![(0, 0), (0, 1)], 9).is_err());
+        assert!(Cage::new(18, vec![(0, 0), (0, 1)], 9).is_err());
+        assert!(Cage::new(5, vec![(0, 0), (0, 0)], 9).is_err());
+        assert!(Cage::new(5, vec![(0, 9)], 9).is_err());
+    }
+}
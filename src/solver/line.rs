@@ -0,0 +1,407 @@
+use crate::board::SudokuBoard;
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::solver::killer_cage::KillerCageEnforcer;
+use crate::solver::SudokuRuleEnforcer;
+
+type Cell = (usize, usize);
+
+/// A constraint on the values along a line or a shape drawn over the grid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Line {
+    /// Values strictly increase from the bulb, the first cell.
+    Thermo(Vec<Cell>),
+    /// The circle holds the sum of the values along the arrow, which may repeat.
+    Arrow { circle: Cell, arrow: Vec<Cell> },
+    /// Two adjacent cells adding up to 10 (X) or 5 (V).
+    Xv { cells: (Cell, Cell), sum: usize },
+    /// German whispers: neighbours on the line differ by at least half the size, 5 on 9x9 boards.
+    Whisper(Vec<Cell>),
+    /// The line holds a set of consecutive values, in any order.
+    Renban(Vec<Cell>),
+    /// The line reads the same from both ends.
+    Palindrome(Vec<Cell>),
+}
+
+/// Checks the cells are on the board, different, and each one touches the next when `path`.
+fn check_cells(cells: &[Cell], size: usize, path: bool) -> Result<(), String> {
+    if let Some(&(row, col)) = cells.iter().find(|&&(row, col)| row >= size || col >= size) {
+        return Err(format!("Invalid position ({row},{col}) in a line."));
+    }
+    if (1..cells.len()).any(|i| cells[..i].contains(&cells[i])) {
+        return Err(format!("A line has repeated cells: {cells:?}."));
+    }
+    let touch = |a: Cell, b: Cell| a.0.abs_diff(b.0).max(a.1.abs_diff(b.1)) == 1;
+    if let Some(pair) = cells
+        .windows(2)
+        .find(|pair| path && !touch(pair[0], pair[1]))
+    {
+        return Err(format!(
+            "The cells {:?} and {:?} of a line don't touch.",
+            pair[0], pair[1]
+        ));
+    }
+    Ok(())
+}
+
+impl Line {
+    pub fn thermo(cells: Vec<Cell>, size: usize) -> Result<Self, String> {
+        if cells.len() < 2 || cells.len() > size {
+            return Err(format!(
+                "A thermometer must have between 2 and {size} cells, found {}.",
+                cells.len()
+            ));
+        }
+        check_cells(&cells, size, true)?;
+        Ok(Line::Thermo(cells))
+    }
+
+    pub fn whisper(cells: Vec<Cell>, size: usize) -> Result<Self, String> {
+        Self::path("whisper", &cells, size, size * size)?;
+        Ok(Line::Whisper(cells))
+    }
+
+    pub fn renban(cells: Vec<Cell>, size: usize) -> Result<Self, String> {
+        Self::path("renban", &cells, size, size)?;
+        Ok(Line::Renban(cells))
+    }
+
+    pub fn palindrome(cells: Vec<Cell>, size: usize) -> Result<Self, String> {
+        Self::path("palindrome", &cells, size, size * size)?;
+        Ok(Line::Palindrome(cells))
+    }
+
+    /// Checks a line of at least 2 and at most `max_len` touching cells.
+    fn path(kind: &str, cells: &[Cell], size: usize, max_len: usize) -> Result<(), String> {
+        if cells.len() < 2 || cells.len() > max_len {
+            return Err(format!(
+                "A {kind} must have between 2 and {max_len} cells, found {}.",
+                cells.len()
+            ));
+        }
+        check_cells(cells, size, true)
+    }
+
+    pub fn arrow(circle: Cell, arrow: Vec<Cell>, size: usize) -> Result<Self, String> {
+        if arrow.is_empty() {
+            return Err(format!("The arrow from {circle:?} has no cells."));
+        }
+        check_cells(&[&[circle], arrow.as_slice()].concat(), size, true)?;
+        Ok(Line::Arrow { circle, arrow })
+    }
+
+    pub fn xv(first: Cell, second: Cell, sum: usize, size: usize) -> Result<Self, String> {
+        if sum != 5 && sum != 10 {
+            return Err(format!("XV pairs add up to 5 or 10, found {sum}."));
+        }
+        if first.0.abs_diff(second.0) + first.1.abs_diff(second.1) != 1 {
+            return Err(format!(
+                "The cells {first:?} and {second:?} are not orthogonally adjacent."
+            ));
+        }
+        check_cells(&[first, second], size, false)?;
+        Ok(Line::Xv {
+            cells: (first, second),
+            sum,
+        })
+    }
+
+    /// A line of the kind `parse_lines` reads, from its `cells()`.
+    pub fn from_kind(kind: &str, cells: Vec<Cell>, size: usize) -> Result<Self, String> {
+        match (kind, cells.as_slice()) {
+            ("thermo", _) => Line::thermo(cells, size),
+            ("arrow", [circle, arrow @ ..]) => Line::arrow(*circle, arrow.to_vec(), size),
+            ("x", &[first, second]) => Line::xv(first, second, 10, size),
+            ("v", &[first, second]) => Line::xv(first, second, 5, size),
+            ("whisper", _) => Line::whisper(cells, size),
+            ("renban", _) => Line::renban(cells, size),
+            ("palindrome", _) => Line::palindrome(cells, size),
+            ("arrow" | "x" | "v", _) => Err(format!(
+                "Invalid number of cells for a {kind} line, found {}.",
+                cells.len()
+            )),
+            _ => Err(format!(
+                "Invalid line kind '{kind}', expected thermo, arrow, x, v, whisper, renban or palindrome."
+            )),
+        }
+    }
+
+    /// The cells of the line, the circle first for an arrow.
+    pub fn cells(&self) -> Vec<Cell> {
+        match self {
+            Line::Thermo(cells)
+            | Line::Whisper(cells)
+            | Line::Renban(cells)
+            | Line::Palindrome(cells) => cells.clone(),
+            Line::Arrow { circle, arrow } => [&[*circle], arrow.as_slice()].concat(),
+            Line::Xv {
+                cells: (first, second),
+                ..
+            } => vec![*first, *second],
+        }
+    }
+
+    /// Narrows the sorted candidates of `cells()` down to the values within the bounds the
+    /// other cells allow, leaving an empty list when a cell has none.
+    fn prune<const N: usize>(&self, candidates: &[Vec<usize>]) -> Vec<Vec<usize>>
+    where
+        ForSize<N>: StorageForSize,
+    {
+        let mut pruned = candidates.to_vec();
+        if pruned.iter().any(Vec::is_empty) {
+            return pruned;
+        }
+        match self {
+            Line::Thermo(_) => {
+                let mut low = 0;
+                for values in &mut pruned {
+                    values.retain(|&value| value > low);
+                    low = values.first().copied().unwrap_or(usize::MAX);
+                }
+                let mut high = usize::MAX;
+                for values in pruned.iter_mut().rev() {
+                    values.retain(|&value| value < high);
+                    high = values.last().copied().unwrap_or(0);
+                }
+            }
+            Line::Arrow { .. } => {
+                let (circle, arrow) = pruned.split_first_mut().unwrap();
+                let min_sum: usize = arrow.iter().map(|values| values[0]).sum();
+                let max_sum: usize = arrow.iter().map(|values| values[values.len() - 1]).sum();
+                circle.retain(|&value| (min_sum..=max_sum).contains(&value));
+                let (Some(&low), Some(&high)) = (circle.first(), circle.last()) else {
+                    return pruned;
+                };
+                for values in arrow {
+                    let others_min = min_sum - values[0];
+                    let others_max = max_sum - values[values.len() - 1];
+                    values.retain(|&value| value + others_min <= high && value + others_max >= low);
+                }
+            }
+            Line::Xv { sum, .. } => {
+                let supported = |values: &[usize], other: &[usize]| -> Vec<usize> {
+                    values
+                        .iter()
+                        .copied()
+                        .filter(|&value| {
+                            sum.checked_sub(value)
+                                .is_some_and(|rest| rest != value && other.contains(&rest))
+                        })
+                        .collect()
+                };
+                pruned = vec![
+                    supported(&candidates[0], &candidates[1]),
+                    supported(&candidates[1], &candidates[0]),
+                ];
+            }
+            Line::Whisper(_) => {
+                let gap = N.div_ceil(2);
+                let far = |value: usize, other: &[usize]| {
+                    other.iter().any(|&other| value.abs_diff(other) >= gap)
+                };
+                for i in 1..pruned.len() {
+                    let previous = pruned[i - 1].clone();
+                    pruned[i].retain(|&value| far(value, &previous));
+                }
+                for i in (0..pruned.len() - 1).rev() {
+                    let next = pruned[i + 1].clone();
+                    pruned[i].retain(|&value| far(value, &next));
+                }
+            }
+            Line::Renban(cells) => {
+                // the values of each run of consecutive values are the ones adding up to its sum.
+                let mut feasible = vec![Vec::new(); cells.len()];
+                for first in 1..=N + 1 - cells.len() {
+                    let run = first..first + cells.len();
+                    let in_run: Vec<Vec<usize>> = candidates
+                        .iter()
+                        .map(|values| {
+                            values
+                                .iter()
+                                .copied()
+                                .filter(|value| run.contains(value))
+                                .collect()
+                        })
+                        .collect();
+                    let run_values =
+                        KillerCageEnforcer::<N>::feasible_distinct_values(&in_run, run.sum());
+                    if run_values.iter().all(|values| !values.is_empty()) {
+                        for (values, run_values) in feasible.iter_mut().zip(run_values) {
+                            values.extend(run_values);
+                        }
+                    }
+                }
+                for values in &mut feasible {
+                    values.sort_unstable();
+                    values.dedup();
+                }
+                pruned = feasible;
+            }
+            Line::Palindrome(cells) => {
+                for i in 0..cells.len() / 2 {
+                    let mirror = cells.len() - 1 - i;
+                    let common: Vec<usize> = candidates[i]
+                        .iter()
+                        .copied()
+                        .filter(|value| candidates[mirror].contains(value))
+                        .collect();
+                    pruned[i].clone_from(&common);
+                    pruned[mirror] = common;
+                }
+            }
+        }
+        pruned
+    }
+}
+
+/// Prunes the candidates along lines and shapes to the values their other cells leave.
+#[derive(Clone)]
+pub struct LineEnforcer<const N: usize> {
+    lines: Vec<Line>,
+}
+
+impl<const N: usize> LineEnforcer<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    pub const fn new(lines: Vec<Line>) -> Self {
+        Self { lines }
+    }
+}
+
+impl<const N: usize> SudokuRuleEnforcer<N> for LineEnforcer<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    fn name(&self) -> &'static str {
+        "LineEnforcer"
+    }
+
+    fn enforce_rule(&mut self, board: &mut SudokuBoard<N>) -> Result<bool, String> {
+        for line in &self.lines {
+            let cells = line.cells();
+            let candidates: Vec<Vec<usize>> = cells
+                .iter()
+                .map(|&(row, col)| board.get_possible_values(row, col).collect())
+                .collect();
+            for (&(row, col), values) in cells.iter().zip(line.prune::<N>(&candidates)) {
+                if values.is_empty() {
+                    return Err(format!(
+                        "Invalid Board, no value of ({row},{col}) fits in {line:?}."
+                    ));
+                }
+                let is_solved = board.retain_values(row, col, &values)?;
+                if is_solved {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Line, LineEnforcer};
+    use crate::board::SudokuBoard;
+    use crate::solver::SudokuRuleEnforcer;
+
+    fn candidates(board: &SudokuBoard<9>, row: usize, col: usize) -> Vec<usize> {
+        board.get_possible_values(row, col).collect()
+    }
+
+    #[test]
+    fn prunes_thermometers() {
+        let thermo = Line::thermo(vec![(0, 0), (0, 1), (1, 2)], 9).unwrap();
+        let mut board = SudokuBoard::<9>::new();
+        board.set(0, 1, 4).unwrap();
+        LineEnforcer::new(vec![thermo])
+            .enforce_rule(&mut board)
+            .unwrap();
+        assert_eq!(candidates(&board, 0, 0), [1, 2, 3]);
+        assert_eq!(candidates(&board, 1, 2), [5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn prunes_arrows() {
+        let arrow = Line::arrow((0, 0), vec![(1, 1), (2, 2)], 9).unwrap();
+        let mut board = SudokuBoard::<9>::new();
+        board.set(1, 1, 6).unwrap();
+        LineEnforcer::new(vec![arrow])
+            .enforce_rule(&mut board)
+            .unwrap();
+        // the circle is at most 9, and the arrow cells of the same box can't repeat the 6.
+        assert_eq!(candidates(&board, 0, 0), [7, 8, 9]);
+        assert_eq!(candidates(&board, 2, 2), [1, 2, 3]);
+    }
+
+    #[test]
+    fn prunes_xv_pairs() {
+        let lines = vec![
+            Line::xv((0, 0), (0, 1), 5, 9).unwrap(),
+            Line::xv((1, 0), (1, 1), 10, 9).unwrap(),
+        ];
+        let mut board = SudokuBoard::<9>::new();
+        board.set(1, 1, 3).unwrap();
+        LineEnforcer::new(lines).enforce_rule(&mut board).unwrap();
+        // (0,1) can't be 3 in the box of the given 3, so (0,0) can't be 2 either.
+        assert_eq!(candidates(&board, 0, 0), [1, 4]);
+        assert_eq!(board.get_value(1, 0), Some(7));
+    }
+
+    #[test]
+    fn prunes_whispers() {
+        let whisper = Line::whisper(vec![(0, 0), (0, 1), (0, 2)], 9).unwrap();
+        let mut board = SudokuBoard::<9>::new();
+        board.set(0, 0, 3).unwrap();
+        LineEnforcer::new(vec![whisper])
+            .enforce_rule(&mut board)
+            .unwrap();
+        assert_eq!(candidates(&board, 0, 1), [8, 9]);
+        assert_eq!(candidates(&board, 0, 2), [1, 2, 4]);
+    }
+
+    #[test]
+    fn prunes_renbans() {
+        let renban = Line::renban(vec![(0, 0), (1, 1), (2, 2)], 9).unwrap();
+        let mut board = SudokuBoard::<9>::new();
+        board.set(0, 0, 8).unwrap();
+        board.set(3, 1, 6).unwrap();
+        board.set(2, 5, 6).unwrap();
+        LineEnforcer::new(vec![renban])
+            .enforce_rule(&mut board)
+            .unwrap();
+        // 6 7 8 is out as neither (1,1) nor (2,2) can be 6: 7 8 9 is the only run left.
+        assert_eq!(candidates(&board, 1, 1), [7, 9]);
+        assert_eq!(candidates(&board, 2, 2), [7, 9]);
+    }
+
+    #[test]
+    fn prunes_palindromes() {
+        let cells = vec![(0, 0), (0, 1), (1, 2), (2, 3), (3, 3)];
+        let palindrome = Line::palindrome(cells, 9).unwrap();
+        let mut board = SudokuBoard::<9>::new();
+        board.set(0, 0, 4).unwrap();
+        board.set(5, 1, 1).unwrap();
+        LineEnforcer::new(vec![palindrome])
+            .enforce_rule(&mut board)
+            .unwrap();
+        assert_eq!(board.get_value(3, 3), Some(4));
+        // (0,1) can't be 1 in the column of (5,1), and neither can its mirror (2,3).
+        assert!(!candidates(&board, 2, 3).contains(&1));
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        assert!(Line::thermo(vec![(0, 0)], 9).is_err());
+        assert!(Line::thermo(vec![(0, 0), (0, 2)], 9).is_err());
+        assert!(Line::thermo(vec![(0, 0), (1, 1), (0, 0)], 9).is_err());
+        assert!(Line::arrow((0, 0), vec![(0, 0)], 9).is_err());
+        assert!(Line::arrow((0, 0), vec![(9, 0)], 9).is_err());
+        assert!(Line::xv((0, 0), (1, 1), 10, 9).is_err());
+        assert!(Line::xv((0, 0), (0, 1), 7, 9).is_err());
+        assert!(Line::whisper(vec![(0, 0)], 9).is_err());
+        assert!(Line::renban(vec![(0, 0), (0, 1), (0, 3)], 9).is_err());
+        assert!(Line::renban((0..9).map(|col| (0, col)).chain([(1, 8)]).collect(), 9).is_err());
+        assert!(Line::palindrome(vec![(0, 0), (0, 0)], 9).is_err());
+    }
+}
@@ -1,19 +1,65 @@
+pub mod adjacency;
+pub mod gattai;
+mod hidden_single;
+pub mod killer_cage;
+pub mod line;
+pub mod outside_clue;
 mod pointing_set;
+pub mod rating;
 mod sub_set;
+mod x_wing;
 
 use crate::board::SudokuBoard;
 use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::solver::adjacency::{Adjacency, AdjacencyEnforcer};
+use crate::solver::hidden_single::HiddenSingleEnforcer;
+use crate::solver::killer_cage::{Cage, KillerCageEnforcer};
+use crate::solver::line::{Line, LineEnforcer};
+use crate::solver::outside_clue::{OutsideClue, OutsideClueEnforcer};
 use crate::solver::pointing_set::PointingSetEnforcer;
 use crate::solver::sub_set::SubSetEnforcer;
+use crate::solver::x_wing::XWingEnforcer;
 use std::fmt::{Debug, Formatter};
 
-trait SudokuRuleEnforcer<const N: usize> {
+trait SudokuRuleEnforcer<const N: usize>: CloneEnforcer<N> {
     fn name(&self) -> &'static str;
     fn enforce_rule(&mut self, board: &mut SudokuBoard<N>) -> Result<bool, String>
     where
         ForSize<N>: StorageForSize;
 }
 
+/// Clones the enforcers along with what they learned, so a search can restore them with the
+/// board when a guess fails.
+trait CloneEnforcer<const N: usize> {
+    fn clone_box(&self) -> Box<dyn SudokuRuleEnforcer<N>>;
+}
+
+impl<const N: usize, T: SudokuRuleEnforcer<N> + Clone + 'static> CloneEnforcer<N> for T {
+    fn clone_box(&self) -> Box<dyn SudokuRuleEnforcer<N>> {
+        Box::new(self.clone())
+    }
+}
+
+impl<const N: usize> Clone for Box<dyn SudokuRuleEnforcer<N>> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// The rules every board follows, variants adding their own.
+fn rule_enforcers<const N: usize>() -> Vec<Box<dyn SudokuRuleEnforcer<N>>>
+where
+    ForSize<N>: StorageForSize,
+{
+    vec![
+        // Box::new(HiddenSetEnforcer::<N>::new()),
+        Box::new(HiddenSingleEnforcer::<N>::new()),
+        Box::new(SubSetEnforcer::<N>::new()),
+        Box::new(PointingSetEnforcer::<N>::new()),
+        Box::new(XWingEnforcer::<N>::new()),
+    ]
+}
+
 pub struct SudokuSolver<const N: usize>
 where
     ForSize<N>: StorageForSize,
@@ -21,6 +67,7 @@ where
     board: SudokuBoard<N>,
     enforcer: Vec<Box<dyn SudokuRuleEnforcer<N>>>,
     pre_solve_error: Option<String>,
+    search: bool,
 }
 
 impl<const N: usize> SudokuSolver<N>
@@ -28,17 +75,51 @@ where
     ForSize<N>: StorageForSize,
 {
     pub fn new() -> Self {
+        Self::from_board(SudokuBoard::<N>::new())
+    }
+
+    pub fn from_board(board: SudokuBoard<N>) -> Self {
         Self {
-            board: SudokuBoard::<N>::new(),
-            enforcer: vec![
-                // Box::new(HiddenSetEnforcer::<N>::new()),
-                Box::new(SubSetEnforcer::<N>::new()),
-                Box::new(PointingSetEnforcer::<N>::new()),
-            ],
+            board,
+            enforcer: rule_enforcers(),
             pre_solve_error: None,
+            search: false,
         }
     }
 
+    /// Adds killer sudoku cages to the rules.
+    pub fn with_cages(mut self, cages: Vec<Cage>) -> Self {
+        self.enforcer
+            .push(Box::new(KillerCageEnforcer::<N>::new(cages)));
+        self
+    }
+
+    /// Adds relations between adjacent cells to the rules, like Kropki dots.
+    pub fn with_adjacencies(mut self, adjacencies: Vec<Adjacency>) -> Self {
+        self.enforcer
+            .push(Box::new(AdjacencyEnforcer::<N>::new(adjacencies)));
+        self
+    }
+
+    /// Adds lines and shapes to the rules, like thermometers, arrows or German whispers.
+    pub fn with_lines(mut self, lines: Vec<Line>) -> Self {
+        self.enforcer.push(Box::new(LineEnforcer::<N>::new(lines)));
+        self
+    }
+
+    /// Adds sandwich and little killer clues to the rules.
+    pub fn with_outside_clues(mut self, clues: Vec<OutsideClue>) -> Self {
+        self.enforcer
+            .push(Box::new(OutsideClueEnforcer::<N>::new(clues)));
+        self
+    }
+
+    /// Guesses values once the rules can't make any progress, backtracking on contradictions.
+    pub const fn with_search(mut self) -> Self {
+        self.search = true;
+        self
+    }
+
     pub fn set(&mut self, row: usize, col: usize, value: usize) {
         if self.pre_solve_error.is_some() {
             return;
@@ -52,27 +133,69 @@ where
         if let Some(error_msg) = self.pre_solve_error {
             return Err(error_msg);
         }
-        if self.board.is_solved() {
-            return Ok(self.board);
-        }
         println!("solving:\n{}\n{:?}", self.board, self.board);
+        self.propagate(true)?;
+        if self.search && !self.board.is_solved() {
+            self.search()?;
+        }
+
+        Ok(self.board)
+    }
+
+    /// Applies the rules until none of them improves the board.
+    fn propagate(&mut self, verbose: bool) -> Result<(), String> {
         let mut iteration = 1;
-        while !self.board.improved.is_empty() {
+        loop {
             self.board.improved.clear();
 
             for rule_enforcer in &mut self.enforcer {
-                let is_solved = rule_enforcer.enforce_rule(&mut self.board);
-                if is_solved.ok().unwrap_or(false) {
+                let is_solved = rule_enforcer.enforce_rule(&mut self.board)?;
+                if is_solved {
                     break;
                 }
-                let x = rule_enforcer.name();
-                println!("iteration: {iteration} solver {x} board:\n{:?}", self.board);
+                if verbose {
+                    let x = rule_enforcer.name();
+                    println!("iteration: {iteration} solver {x} board:\n{:?}", self.board);
+                }
+            }
+            if verbose {
+                println!("Improvements: {:?}", self.board.improved);
+            }
+            if self.board.improved.is_empty() {
+                return Ok(());
             }
-            println!("Improvements: {:?}", self.board.improved);
             iteration += 1;
         }
+    }
 
-        Ok(self.board)
+    /// Tries every value of the cell with the fewest candidates, propagating and searching
+    /// further from each guess until the board is solved.
+    fn search(&mut self) -> Result<(), String> {
+        let Some((row, col)) = (0..N)
+            .flat_map(|row| (0..N).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.board.get_value(row, col).is_none())
+            .min_by_key(|&(row, col)| self.board.get_possible_values(row, col).count())
+        else {
+            return Ok(());
+        };
+
+        let values: Vec<_> = self.board.get_possible_values(row, col).collect();
+        for value in values {
+            let saved = (self.board.clone(), self.enforcer.clone());
+            let guess = self
+                .board
+                .set(row, col, value)
+                .and_then(|_| self.propagate(false))
+                .and_then(|()| self.search());
+            if guess.is_ok() && self.board.is_solved() {
+                return Ok(());
+            }
+            (self.board, self.enforcer) = saved;
+        }
+        Err(format!(
+            "Invalid Board, no value fits at ({row},{col}) among {:?}.",
+            self.board.get_possible_values(row, col).collect::<Vec<_>>()
+        ))
     }
 }
 
@@ -84,3 +207,565 @@ where
         write!(f, "{:?}", self.board)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SudokuSolver;
+    use crate::any_board::{AnyBoard, AnySolver};
+    use crate::board::SudokuBoard;
+    use crate::dlx::dlx_solver::DlxSolver;
+    use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+    use crate::puzzle::{
+        parse_cages, parse_dots, parse_inequalities, parse_layout, parse_lines,
+        parse_outside_clues, parse_parity, parse_puzzle, to_puzzle_string,
+    };
+    use crate::region::{ChessConstraint, RegionLayout};
+
+    const PUZZLE_4: &str = "..3..4....1..2..";
+
+    const PUZZLE_6: &str = "1.3...45.......5...61..4......6...45";
+
+    const PUZZLE_16: &str = ".9....1.8B..E4..1....8EFC54.....63.7B........82...5...G.1.9..A6.G8..2.5..A.4F....4.5.D.....E..1C.2...BFG9..7...E.EF.173..862...D..G.C123.EB.7...3..27.9..4......FC......7..91654...8.F.......CA2....F2B1A.ED...9....6A....C.D...E.A....9..3.21.FB.7F5.....G16.C.";
+
+    const PUZZLE_25: &str = "..14B.6.89GC.HPA.D.JLK.N5HCL287N.GFODI9.B.4.P..EA.G.NM.PO..L8A.71HKEFI...92P9I.E.1D..J..KB..N3L.GO....5O.B...A.E2NL..9M8.H1D7.K..JH.NI4..8F..LO.13.G.....7A6M..K3O..I.GC9.NB..HEID.N5J9.GC..4K3P.B6A8.2M5O.1G.P2.3DH.BM.4AN.IF6K.LH.83C7.BDAGE6NF.KIM.JP.43.4E1K.P7.6.DI8L.B.A2MNGFN8.GI9B..O.KLA.P165..E..DD.7K.35G16..C2.N98E4.PAHICP6B.D...H..4JG..7KF..9O..MOH.I24LNE17PFD..JC85K6B.NG5LF.B2P1JOE4C8I7D....K..BP.L..DC.IN3H2JF4...58.4.H3.J.E6.PF.L9..5..D72BOJA8IO..5K...6MD9BPG3E.FL.92FDKO.7NMB8A.5..1LH..3..7F.C5M.O..4N.GJ1.L6BK.8.9..EL...H.29..8..DJCG54MIAO63JHGKC..I2.1E4A.89FD..NI.2N41....L.FOA....5G.BP68.K9MAL.4I53.DC.F2...OJE1";
+
+    const JIGSAW_LAYOUT: &str = "
+        122222223
+        112113233
+        411133333
+        414555556
+        444556666
+        477586996
+        477588996
+        477888896
+        777889999";
+
+    const JIGSAW_PUZZLE: &str =
+        "...5....14...3..8.............9..3...1.6.......9......3.....7...7..5..9.......8.2";
+
+    const JIGSAW_SOLUTION: &str =
+        "632548971491237685857169243786912354914673528569821437325486719278354196143795862";
+
+    const DIAGONAL_PUZZLE: &str =
+        ".6.2......8......1...4..57..1.....3.......8...........4.6.753.28...39............";
+
+    const DIAGONAL_SOLUTION: &str =
+        "561287943784593621239416578915768234627341859348952167496175382872639415153824796";
+
+    const WINDOKU_PUZZLE: &str =
+        ".6.....4.........1...95.7.84..1...87....4.6...........8...9...23....8..4.........";
+
+    const WINDOKU_SOLUTION: &str =
+        "561287943978634251234951768452169387783542619619873425845396172326718594197425836";
+
+    const DISJOINT_PUZZLE: &str =
+        "...5.....7...48..2.6......8.19.....6...8...5.2.......9.87...6........4.1...63....";
+
+    const DISJOINT_SOLUTION: &str =
+        "148562793793148562562793148819254376376819254254376819987421635635987421421635987";
+
+    const KILLER_CAGES: &str = "
+        7 r9c6 r9c7
+        17 r8c2 r8c1 r8c3
+        17 r8c7 r8c8 r7c8
+        14 r9c1 r9c2 r9c3 r9c4
+        10 r1c4 r1c3
+        21 r3c9 r2c9 r3c8
+        12 r5c1 r4c1
+        16 r5c8 r5c9 r6c9
+        4 r7c5 r8c5
+        15 r6c3 r7c3 r7c4 r7c2
+        11 r5c7 r4c7
+        24 r1c5 r1c6 r2c5
+        25 r3c3 r4c3 r5c3 r5c2
+        18 r8c6 r7c6 r7c7
+        5 r4c8 r4c9
+        11 r2c6 r3c6 r4c6 r2c7
+        17 r6c1 r7c1 r6c2
+        8 r9c5
+        20 r2c1 r1c1 r2c2 r2c3
+        10 r4c5 r3c5
+        19 r6c4 r6c5 r5c4
+        9 r7c9 r8c9
+        16 r9c9 r9c8
+        5 r3c7
+        11 r3c4 r4c4 r2c4
+        12 r5c5 r5c6 r6c6
+        10 r3c1 r3c2
+        3 r1c2
+        7 r2c8 r1c8 r1c9
+        9 r1c7
+        13 r6c8 r6c7
+        4 r8c4
+        5 r4c2";
+
+    const KILLER_SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    const KROPKI_DOTS: &str = "
+        negative
+        white r1c1 r2c1
+        white r1c2 r1c3
+        black r1c3 r2c3
+        white r1c4 r1c5
+        white r1c5 r1c6
+        white r1c6 r1c7
+        white r1c8 r1c9
+        white r2c1 r2c2
+        white r2c3 r2c4
+        white r2c7 r2c8
+        black r2c8 r2c9
+        white r2c9 r3c9
+        white r3c2 r3c3
+        white r3c3 r4c3
+        white r3c4 r3c5
+        black r3c5 r3c6
+        white r3c6 r4c6
+        white r3c7 r3c8
+        white r3c7 r4c7
+        white r3c8 r3c9
+        black r4c1 r5c1
+        white r4c4 r4c5
+        white r4c4 r5c4
+        white r4c5 r5c5
+        black r4c7 r4c8
+        white r4c8 r4c9
+        black r5c1 r5c2
+        white r5c2 r6c2
+        black r5c3 r6c3
+        white r5c4 r6c4
+        white r5c6 r6c6
+        white r5c7 r6c7
+        black r6c5 r6c6
+        white r6c5 r7c5
+        black r6c6 r6c7
+        white r6c8 r6c9
+        white r7c4 r8c4
+        black r7c8 r7c9
+        white r7c9 r8c9
+        white r8c1 r9c1
+        white r8c2 r8c3
+        black r8c2 r9c2
+        black r8c4 r9c4
+        black r8c7 r8c8
+        white r9c1 r9c2
+        white r9c2 r9c3";
+
+    const LINES: &str = "
+        thermo r8c5 r7c5 r7c6 r6c7 r5c8
+        thermo r2c3 r1c4 r2c5
+        thermo r1c3 r2c2 r3c3 r3c2
+        thermo r5c9 r6c8 r7c8
+        thermo r4c4 r5c4 r4c3
+        thermo r6c6 r5c5 r4c5
+        arrow r1c5 r2c4 r3c5 r3c6
+        arrow r5c1 r6c2 r6c3
+        arrow r3c8 r4c7 r4c8
+        x r1c2 r2c2
+        x r1c3 r1c4
+        x r1c7 r1c8
+        v r1c8 r2c8
+        x r1c9 r2c9
+        x r2c3 r3c3
+        x r2c4 r2c5
+        x r2c8 r3c8
+        x r3c1 r3c2
+        x r3c4 r4c4
+        x r3c5 r4c5
+        x r3c9 r4c9
+        v r4c6 r4c7
+        v r4c8 r4c9
+        x r5c6 r5c7
+        x r5c8 r5c9
+        v r6c5 r7c5
+        x r6c7 r7c7
+        x r6c9 r7c9
+        x r7c5 r7c6
+        x r7c7 r7c8
+        x r8c1 r8c2
+        v r8c1 r9c1
+        v r8c4 r8c5
+        x r8c5 r8c6
+        x r8c8 r9c8
+        x r9c4 r9c5";
+
+    const WHISPER_PUZZLE: &str =
+        "......9.....1.........4...785.7..........3..1...............28......9.35.........";
+
+    const WHISPERS: &str = "
+        renban r2c9 r3c9 r3c8 r2c8 r3c7
+        whisper r9c4 r9c5 r8c5 r9c6
+        whisper r5c4 r6c5 r6c4
+        renban r7c5 r8c4 r9c3
+        renban r2c2 r1c1 r2c1
+        palindrome r6c2 r6c3 r7c3
+        palindrome r4c1 r3c2 r3c3
+        palindrome r6c9 r7c8 r8c7";
+
+    const SANDWICH_PUZZLE: &str =
+        ".............................9.............9........5.......2...8................";
+
+    const SANDWICH_CLUES: &str = "
+        sandwich r1 0
+        sandwich r2 0
+        sandwich r3 0
+        sandwich r4 13
+        sandwich r5 0
+        sandwich r6 3
+        sandwich r7 6
+        sandwich r8 0
+        sandwich r9 7
+        sandwich c1 19
+        sandwich c2 7
+        sandwich c3 9
+        sandwich c4 18
+        sandwich c5 20
+        sandwich c6 14
+        sandwich c7 35
+        sandwich c8 12
+        sandwich c9 15
+        little-killer r1c3 se 26
+        little-killer r1c6 se 20
+        little-killer r4c1 se 25";
+
+    const PARITY: &str = "
+        ...e.e...
+        ..e.o..ee
+        o.e....e.
+        e...e.ee.
+        eeee.....
+        ......e..
+        o......e.
+        ..o.oo..o
+        .e.eee...";
+
+    const INEQUALITIES: &str = "
+        r1c3 > r2c3
+        r1c4 < r1c5
+        r1c5 < r1c6
+        r1c5 < r2c5
+        r1c6 < r1c7
+        r2c6 > r3c6
+        r2c8 < r2c9
+        r2c8 < r3c8
+        r2c9 > r3c9
+        r3c1 < r4c1
+        r3c4 < r3c5
+        r3c5 > r3c6
+        r3c5 < r4c5
+        r3c8 < r3c9
+        r4c1 > r5c1
+        r4c2 < r4c3
+        r4c5 > r5c5
+        r4c7 > r4c8
+        r4c8 < r4c9
+        r5c4 < r6c4
+        r5c7 < r5c8
+        r6c2 < r6c3
+        r7c3 < r8c3
+        r7c9 < r8c9
+        r8c1 < r8c2
+        r8c6 > r9c6
+        r8c7 > r9c7
+        r9c1 < r9c2
+        r9c2 < r9c3";
+
+    const ANTI_KNIGHT_PUZZLE: &str =
+        ".6........8..........3......5.....86......1.........3.2.8.9...4....2.............";
+
+    const ANTI_KNIGHT_SOLUTION: &str =
+        "561287943387549621429316578752931486834672195196458237278195364645823719913764852";
+
+    const ANTI_KING_PUZZLE: &str =
+        ".6.....4..8...3..1....4.57....8.6.19....1...........3.8.5.7.2..9...65............";
+
+    const ANTI_KING_SOLUTION: &str =
+        "561287943487593621293641578752836419349712865618459732835174296924365187176928354";
+
+    fn solves_end_to_end<const N: usize>(puzzle: &str)
+    where
+        ForSize<N>: StorageForSize,
+    {
+        let (size, givens) = parse_puzzle(puzzle).unwrap();
+        assert_eq!(size, N);
+
+        let mut solver = SudokuSolver::<N>::new();
+        let mut dlx_solver = DlxSolver::<N>::new();
+        for &(row, col, value) in &givens {
+            solver.set(row, col, value);
+            dlx_solver.set(row, col, value);
+        }
+        assert!(dlx_solver.has_unique_solution());
+
+        let board = solver.solve().unwrap();
+        let expected = dlx_solver.solve().unwrap();
+        assert!(board.is_solved());
+        for row in 0..N {
+            for col in 0..N {
+                assert_eq!(board.get_value(row, col), expected.get_value(row, col));
+            }
+        }
+    }
+
+    /// A grid shifting every row of boxes, with three cells missing in each row and column.
+    fn patterned_puzzle(size: usize) -> String {
+        let box_size = size.isqrt();
+        let givens: Vec<_> = (0..size)
+            .flat_map(|row| (0..size).map(move |col| (row, col)))
+            .filter(|&(row, col)| (col + 3 * row) % size >= 3)
+            .map(|(row, col)| {
+                let value = (box_size * (row % box_size) + row / box_size + col) % size + 1;
+                (row, col, value)
+            })
+            .collect();
+        to_puzzle_string(size, &givens)
+    }
+
+    #[test]
+    fn solves_4x4() {
+        solves_end_to_end::<4>(PUZZLE_4);
+    }
+
+    #[test]
+    fn solves_6x6() {
+        solves_end_to_end::<6>(PUZZLE_6);
+    }
+
+    #[test]
+    fn solves_16x16() {
+        solves_end_to_end::<16>(PUZZLE_16);
+    }
+
+    #[test]
+    fn solves_25x25() {
+        solves_end_to_end::<25>(PUZZLE_25);
+    }
+
+    #[test]
+    fn solves_64x64() {
+        solves_end_to_end::<64>(&patterned_puzzle(64));
+    }
+
+    #[test]
+    fn solves_jigsaw() {
+        let layout = parse_layout(JIGSAW_LAYOUT).unwrap();
+        let board = AnyBoard::from_puzzle(JIGSAW_PUZZLE, layout.clone()).unwrap();
+        assert!(board.has_unique_solution());
+
+        let solved = AnySolver::from_board(board.clone()).solve().unwrap();
+        let expected = AnyBoard::from_puzzle(JIGSAW_SOLUTION, layout).unwrap();
+        assert!(solved.is_solved());
+        assert_eq!(solved.to_string(), expected.to_string());
+        assert_eq!(board.solve_dlx().unwrap().to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn jigsaw_boxes_are_enforced() {
+        // (0,5) and (1,6) are in different boxes of a regular board, but in the same jigsaw box.
+        let puzzle = format!("{}1{}1{}", ".".repeat(5), ".".repeat(9), ".".repeat(65));
+        assert!(AnyBoard::from_puzzle(&puzzle, RegionLayout::for_size(9)).is_ok());
+        let layout = parse_layout(JIGSAW_LAYOUT).unwrap();
+        assert!(AnyBoard::from_puzzle(&puzzle, layout).is_err());
+    }
+
+    #[test]
+    fn solves_diagonal() {
+        let layout = RegionLayout::for_size(9).with_diagonals();
+        solves_with_layout(DIAGONAL_PUZZLE, DIAGONAL_SOLUTION, layout);
+    }
+
+    #[test]
+    fn diagonal_placements_propagate() {
+        let layout = RegionLayout::for_size(9).with_diagonals();
+        let puzzle = format!("1{}1{}", ".".repeat(39), ".".repeat(40));
+        assert!(AnyBoard::from_puzzle(&puzzle, RegionLayout::for_size(9)).is_ok());
+        assert!(AnyBoard::from_puzzle(&puzzle, layout).is_err());
+    }
+
+    #[test]
+    fn solves_windoku() {
+        let layout = RegionLayout::for_size(9).with_windows().unwrap();
+        solves_with_layout(WINDOKU_PUZZLE, WINDOKU_SOLUTION, layout);
+    }
+
+    #[test]
+    fn solves_disjoint_groups() {
+        let layout = RegionLayout::for_size(9).with_disjoint_groups().unwrap();
+        solves_with_layout(DISJOINT_PUZZLE, DISJOINT_SOLUTION, layout);
+    }
+
+    #[test]
+    fn disjoint_placements_propagate() {
+        // (0,0) and (3,3) are the first cells of different boxes.
+        let layout = RegionLayout::for_size(9).with_disjoint_groups().unwrap();
+        let puzzle = format!("1{}1{}", ".".repeat(29), ".".repeat(50));
+        assert!(AnyBoard::from_puzzle(&puzzle, RegionLayout::for_size(9)).is_ok());
+        assert!(AnyBoard::from_puzzle(&puzzle, layout).is_err());
+    }
+
+    #[test]
+    fn solves_killer() {
+        let cages = parse_cages(KILLER_CAGES, 9).unwrap();
+        let board = AnyBoard::from_puzzle(&".".repeat(81), RegionLayout::for_size(9)).unwrap();
+        let solved = AnySolver::from_board(board)
+            .with_cages(cages)
+            .with_search()
+            .solve()
+            .unwrap();
+
+        let expected = AnyBoard::from_puzzle(KILLER_SOLUTION, RegionLayout::for_size(9)).unwrap();
+        assert!(solved.is_solved());
+        assert_eq!(solved.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn complete_grids_follow_the_rules() {
+        // r1c1 and r1c2 hold 5 and 3.
+        let board = AnyBoard::from_puzzle(KILLER_SOLUTION, RegionLayout::for_size(9)).unwrap();
+        let cages = parse_cages("8 r1c1 r1c2", 9).unwrap();
+        assert!(AnySolver::from_board(board.clone())
+            .with_cages(cages)
+            .solve()
+            .is_ok());
+
+        let cages = parse_cages("3 r1c1 r1c2", 9).unwrap();
+        assert!(AnySolver::from_board(board.clone())
+            .with_cages(cages)
+            .solve()
+            .is_err());
+        let lines = parse_lines("thermo r1c1 r1c2", 9).unwrap();
+        assert!(AnySolver::from_board(board.clone())
+            .with_lines(lines)
+            .solve()
+            .is_err());
+        let dots = parse_dots("white r1c1 r1c2", 9).unwrap();
+        assert!(AnySolver::from_board(board.clone())
+            .with_adjacencies(dots)
+            .solve()
+            .is_err());
+        let clues = parse_outside_clues("sandwich r1 5", 9).unwrap();
+        assert!(AnySolver::from_board(board)
+            .with_outside_clues(clues)
+            .solve()
+            .is_err());
+    }
+
+    #[test]
+    fn solves_kropki() {
+        // every dot is given with the negative constraint, the killer solution is the only one.
+        let dots = parse_dots(KROPKI_DOTS, 9).unwrap();
+        let board = AnyBoard::from_puzzle(&".".repeat(81), RegionLayout::for_size(9)).unwrap();
+        let solved = AnySolver::from_board(board)
+            .with_adjacencies(dots)
+            .with_search()
+            .solve()
+            .unwrap();
+
+        let expected = AnyBoard::from_puzzle(KILLER_SOLUTION, RegionLayout::for_size(9)).unwrap();
+        assert!(solved.is_solved());
+        assert_eq!(solved.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn solves_lines() {
+        // thermometers, arrows and every XV pair of the killer solution, without givens.
+        let lines = parse_lines(LINES, 9).unwrap();
+        let board = AnyBoard::from_puzzle(&".".repeat(81), RegionLayout::for_size(9)).unwrap();
+        let solved = AnySolver::from_board(board)
+            .with_lines(lines)
+            .with_search()
+            .solve()
+            .unwrap();
+
+        let expected = AnyBoard::from_puzzle(KILLER_SOLUTION, RegionLayout::for_size(9)).unwrap();
+        assert!(solved.is_solved());
+        assert_eq!(solved.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn restoring_the_rules_reapplies_them() {
+        // the rules solve it, but only by applying the subsets and pointing sets they find.
+        let puzzle =
+            "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+        let (_, givens) = parse_puzzle(puzzle).unwrap();
+        let mut solver = SudokuSolver::<9>::from_board(SudokuBoard::from_givens(&givens).unwrap());
+        let saved = (solver.board.clone(), solver.enforcer.clone());
+        solver.propagate(false).unwrap();
+        assert!(solver.board.is_solved());
+        let propagated = format!("{:?}", solver.board);
+
+        (solver.board, solver.enforcer) = saved;
+        solver.propagate(false).unwrap();
+        assert_eq!(format!("{:?}", solver.board), propagated);
+    }
+
+    /// Without search the solver only makes deductions, so reaching the solution that the givens
+    /// alone don't fix shows the rules make it unique.
+    fn solves_by_rules(board: AnyBoard, with_rules: impl FnOnce(AnySolver) -> AnySolver) {
+        assert!(!board.has_unique_solution());
+        let solved = with_rules(AnySolver::from_board(board)).solve().unwrap();
+
+        let expected = AnyBoard::from_puzzle(KILLER_SOLUTION, RegionLayout::for_size(9)).unwrap();
+        assert!(solved.is_solved());
+        assert_eq!(solved.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn solves_whispers() {
+        let lines = parse_lines(WHISPERS, 9).unwrap();
+        let board = AnyBoard::from_puzzle(WHISPER_PUZZLE, RegionLayout::for_size(9)).unwrap();
+        solves_by_rules(board, |solver| solver.with_lines(lines));
+    }
+
+    #[test]
+    fn solves_sandwich() {
+        let clues = parse_outside_clues(SANDWICH_CLUES, 9).unwrap();
+        let board = AnyBoard::from_puzzle(SANDWICH_PUZZLE, RegionLayout::for_size(9)).unwrap();
+        solves_by_rules(board, |solver| solver.with_outside_clues(clues));
+    }
+
+    #[test]
+    fn solves_parity_and_inequalities() {
+        // no givens, the parity and the signs are enough.
+        let mut board = AnyBoard::from_puzzle(&".".repeat(81), RegionLayout::for_size(9)).unwrap();
+        for (row, col, parity) in parse_parity(PARITY, 9).unwrap() {
+            board.constrain_parity(row, col, parity).unwrap();
+        }
+        let signs = parse_inequalities(INEQUALITIES, 9).unwrap();
+        solves_by_rules(board, |solver| solver.with_adjacencies(signs));
+    }
+
+    fn solves_with_layout(puzzle: &str, solution: &str, layout: RegionLayout) {
+        let board = AnyBoard::from_puzzle(puzzle, layout.clone()).unwrap();
+        assert!(board.has_unique_solution());
+        assert!(!AnyBoard::from_puzzle(puzzle, RegionLayout::for_size(9))
+            .unwrap()
+            .has_unique_solution());
+
+        let solved = AnySolver::from_board(board).solve().unwrap();
+        let expected = AnyBoard::from_puzzle(solution, layout).unwrap();
+        assert!(solved.is_solved());
+        assert_eq!(solved.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn solves_anti_knight() {
+        let layout = RegionLayout::for_size(9).with_chess_constraint(ChessConstraint::AntiKnight);
+        solves_with_layout(ANTI_KNIGHT_PUZZLE, ANTI_KNIGHT_SOLUTION, layout);
+    }
+
+    #[test]
+    fn solves_anti_king() {
+        let layout = RegionLayout::for_size(9).with_chess_constraint(ChessConstraint::AntiKing);
+        solves_with_layout(ANTI_KING_PUZZLE, ANTI_KING_SOLUTION, layout);
+    }
+
+    #[test]
+    fn chess_placements_propagate() {
+        // (0,2) and (1,4) are a knight's move apart, in different boxes.
+        let puzzle = format!("..1{}1{}", ".".repeat(10), ".".repeat(67));
+        assert!(AnyBoard::from_puzzle(&puzzle, RegionLayout::for_size(9)).is_ok());
+        let layout = RegionLayout::for_size(9).with_chess_constraint(ChessConstraint::AntiKnight);
+        assert!(AnyBoard::from_puzzle(&puzzle, layout).is_err());
+    }
+}
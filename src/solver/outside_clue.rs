@@ -0,0 +1,327 @@
+use crate::board::SudokuBoard;
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::region::RegionLayout;
+use crate::solver::killer_cage::KillerCageEnforcer;
+use crate::solver::SudokuRuleEnforcer;
+use std::collections::HashSet;
+
+type Cell = (usize, usize);
+
+/// A clue written outside of the grid, about the cells of the row, column or diagonal it faces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OutsideClue {
+    /// The values between the 1 and the largest value of a row or column add up to `sum`.
+    Sandwich { cells: Vec<Cell>, sum: usize },
+    /// The values along a diagonal add up to `sum`, and may repeat where the cells don't see each
+    /// other.
+    LittleKiller { cells: Vec<Cell>, sum: usize },
+}
+
+impl OutsideClue {
+    pub fn row_sandwich(row: usize, sum: usize, size: usize) -> Result<Self, String> {
+        Self::sandwich((0..size).map(|col| (row, col)).collect(), sum, size)
+    }
+
+    pub fn col_sandwich(col: usize, sum: usize, size: usize) -> Result<Self, String> {
+        Self::sandwich((0..size).map(|row| (row, col)).collect(), sum, size)
+    }
+
+    fn sandwich(cells: Vec<Cell>, sum: usize, size: usize) -> Result<Self, String> {
+        if let Some(&(row, col)) = cells.iter().find(|&&(row, col)| row >= size || col >= size) {
+            return Err(format!("Invalid position ({row},{col}) for a sandwich."));
+        }
+        let max_sum: usize = (2..size).sum();
+        if size < 3 || sum > max_sum {
+            return Err(format!(
+                "A sandwich of a {size}x{size} board adds up to at most {max_sum}, found {sum}."
+            ));
+        }
+        Ok(OutsideClue::Sandwich { cells, sum })
+    }
+
+    /// The diagonal starting from `start`, a cell on the edge facing the clue, and going in the
+    /// `(row, col)` direction, like `(1, 1)` down and right.
+    pub fn little_killer(
+        start: Cell,
+        direction: (isize, isize),
+        sum: usize,
+        size: usize,
+    ) -> Result<Self, String> {
+        if direction.0.abs() != 1 || direction.1.abs() != 1 {
+            return Err(format!("Invalid diagonal direction {direction:?}."));
+        }
+        let step = |(row, col): Cell, sign: isize| -> Option<Cell> {
+            let row = row.checked_add_signed(sign * direction.0)?;
+            let col = col.checked_add_signed(sign * direction.1)?;
+            (row < size && col < size).then_some((row, col))
+        };
+        if start.0 >= size || start.1 >= size || step(start, -1).is_some() {
+            return Err(format!(
+                "A little killer clue starts from the edge of the board, found {start:?}."
+            ));
+        }
+
+        let cells: Vec<_> = std::iter::successors(Some(start), |&cell| step(cell, 1)).collect();
+        if sum < cells.len() || sum > size * cells.len() {
+            return Err(format!(
+                "A diagonal of {} cells can't add up to {sum}.",
+                cells.len()
+            ));
+        }
+        Ok(OutsideClue::LittleKiller { cells, sum })
+    }
+
+    pub fn cells(&self) -> &[Cell] {
+        match self {
+            OutsideClue::Sandwich { cells, .. } | OutsideClue::LittleKiller { cells, .. } => cells,
+        }
+    }
+
+    /// The values every cell can take in some arrangement of the line matching the clue.
+    fn feasible_values<const N: usize>(
+        &self,
+        candidates: &[Vec<usize>],
+        layout: &RegionLayout,
+    ) -> Vec<Vec<usize>>
+    where
+        ForSize<N>: StorageForSize,
+    {
+        match self {
+            OutsideClue::Sandwich { sum, .. } => sandwich_values::<N>(candidates, *sum),
+            OutsideClue::LittleKiller { cells, sum } => {
+                let sees: Vec<Vec<bool>> = cells
+                    .iter()
+                    .map(|&cell| {
+                        cells
+                            .iter()
+                            .map(|&other| layout.sees(cell, other))
+                            .collect()
+                    })
+                    .collect();
+                little_killer_values(candidates, *sum, &sees)
+            }
+        }
+    }
+}
+
+/// The sum of the first cells of a diagonal, and the values of the ones seen by a later cell.
+type DiagonalState = (usize, Vec<(usize, usize)>);
+
+/// Like `KillerCageEnforcer::feasible_repeated_values`, going over the sums of the first cells,
+/// but keeping the values of the cells seen by a later one so `sees[i][j]` cells differ.
+fn little_killer_values(
+    candidates: &[Vec<usize>],
+    sum: usize,
+    sees: &[Vec<bool>],
+) -> Vec<Vec<usize>> {
+    let fits = |i: usize, value: usize, (partial, seen): &DiagonalState| {
+        partial + value <= sum && !seen.iter().any(|&(j, other)| other == value && sees[j][i])
+    };
+    let next_state = |i: usize, value: usize, (partial, seen): &DiagonalState| -> DiagonalState {
+        let mut seen = seen.clone();
+        seen.push((i, value));
+        seen.retain(|&(j, _)| (i + 1..candidates.len()).any(|k| sees[j][k]));
+        (partial + value, seen)
+    };
+
+    let mut layers: Vec<HashSet<DiagonalState>> = vec![HashSet::from([(0, Vec::new())])];
+    for (i, cell_candidates) in candidates.iter().enumerate() {
+        let mut next = HashSet::new();
+        for state in layers.last().unwrap() {
+            for &value in cell_candidates
+                .iter()
+                .filter(|&&value| fits(i, value, state))
+            {
+                next.insert(next_state(i, value, state));
+            }
+        }
+        layers.push(next);
+    }
+
+    let mut feasible = vec![Vec::new(); candidates.len()];
+    let mut reachable: HashSet<DiagonalState> = layers
+        .pop()
+        .unwrap()
+        .into_iter()
+        .filter(|(partial, _)| *partial == sum)
+        .collect();
+    for (i, layer) in layers.iter().enumerate().rev() {
+        let mut previous = HashSet::new();
+        for state in layer {
+            for &value in candidates[i].iter().filter(|&&value| fits(i, value, state)) {
+                if reachable.contains(&next_state(i, value, state)) {
+                    if !feasible[i].contains(&value) {
+                        feasible[i].push(value);
+                    }
+                    previous.insert(state.clone());
+                }
+            }
+        }
+        reachable = previous;
+    }
+    feasible
+}
+
+/// Goes over the positions of the 1 and the `N` in the line, keeping the values of the cells
+/// between them adding up to `sum` and any other value outside of them.
+fn sandwich_values<const N: usize>(candidates: &[Vec<usize>], sum: usize) -> Vec<Vec<usize>>
+where
+    ForSize<N>: StorageForSize,
+{
+    let fillings: Vec<Vec<usize>> = candidates
+        .iter()
+        .map(|values| {
+            values
+                .iter()
+                .copied()
+                .filter(|&value| value != 1 && value != N)
+                .collect()
+        })
+        .collect();
+
+    let mut feasible = vec![Vec::new(); candidates.len()];
+    let mut add = |i: usize, values: &[usize]| {
+        for &value in values {
+            if !feasible[i].contains(&value) {
+                feasible[i].push(value);
+            }
+        }
+    };
+    for (one, largest) in (0..candidates.len())
+        .flat_map(|one| (0..candidates.len()).map(move |largest| (one, largest)))
+        .filter(|&(one, largest)| {
+            candidates[one].contains(&1) && candidates[largest].contains(&N) && one != largest
+        })
+    {
+        let between = one.min(largest) + 1..one.max(largest);
+        let between_values =
+            KillerCageEnforcer::<N>::feasible_distinct_values(&fillings[between.clone()], sum);
+        let is_feasible = if between.is_empty() {
+            sum == 0
+        } else {
+            between_values.iter().all(|values| !values.is_empty())
+        };
+        let outside =
+            (0..candidates.len()).filter(|&i| i != one && i != largest && !between.contains(&i));
+        if !is_feasible || outside.clone().any(|i| fillings[i].is_empty()) {
+            continue;
+        }
+
+        add(one, &[1]);
+        add(largest, &[N]);
+        for (i, values) in between.clone().zip(&between_values) {
+            add(i, values);
+        }
+        for i in outside {
+            add(i, &fillings[i]);
+        }
+    }
+    feasible
+}
+
+/// Prunes the candidates of the rows, columns and diagonals with outside clues to the values
+/// of the arrangements matching them.
+#[derive(Clone)]
+pub struct OutsideClueEnforcer<const N: usize> {
+    clues: Vec<OutsideClue>,
+}
+
+impl<const N: usize> OutsideClueEnforcer<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    pub const fn new(clues: Vec<OutsideClue>) -> Self {
+        Self { clues }
+    }
+}
+
+impl<const N: usize> SudokuRuleEnforcer<N> for OutsideClueEnforcer<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    fn name(&self) -> &'static str {
+        "OutsideClueEnforcer"
+    }
+
+    fn enforce_rule(&mut self, board: &mut SudokuBoard<N>) -> Result<bool, String> {
+        for clue in &self.clues {
+            let candidates: Vec<Vec<usize>> = clue
+                .cells()
+                .iter()
+                .map(|&(row, col)| board.get_possible_values(row, col).collect())
+                .collect();
+            let feasible = clue.feasible_values::<N>(&candidates, board.layout());
+            for (&(row, col), values) in clue.cells().iter().zip(feasible) {
+                if values.is_empty() {
+                    return Err(format!(
+                        "Invalid Board, no value of ({row},{col}) matches {clue:?}."
+                    ));
+                }
+                let is_solved = board.retain_values(row, col, &values)?;
+                if is_solved {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OutsideClue, OutsideClueEnforcer};
+    use crate::board::SudokuBoard;
+    use crate::solver::SudokuRuleEnforcer;
+
+    fn candidates(board: &SudokuBoard<9>, row: usize, col: usize) -> Vec<usize> {
+        board.get_possible_values(row, col).collect()
+    }
+
+    #[test]
+    fn prunes_sandwiches() {
+        // only 2 fits between the 1 and the 9, who are two cells apart.
+        let mut board = SudokuBoard::<9>::new();
+        board.set(0, 0, 1).unwrap();
+        OutsideClueEnforcer::new(vec![OutsideClue::row_sandwich(0, 2, 9).unwrap()])
+            .enforce_rule(&mut board)
+            .unwrap();
+        assert_eq!(board.get_value(0, 1), Some(2));
+        assert_eq!(board.get_value(0, 2), Some(9));
+
+        // a sum of 0 puts the 1 and the 9 side by side.
+        let mut board = SudokuBoard::<9>::new();
+        board.set(4, 0, 9).unwrap();
+        OutsideClueEnforcer::new(vec![OutsideClue::col_sandwich(0, 0, 9).unwrap()])
+            .enforce_rule(&mut board)
+            .unwrap();
+        assert_eq!(candidates(&board, 3, 0), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(!candidates(&board, 2, 0).contains(&1));
+        assert!(!candidates(&board, 6, 0).contains(&1));
+    }
+
+    #[test]
+    fn prunes_little_killers() {
+        let clue = OutsideClue::little_killer((0, 4), (1, 1), 7, 9).unwrap();
+        assert_eq!(clue.cells(), [(0, 4), (1, 5), (2, 6), (3, 7), (4, 8)]);
+        let mut board = SudokuBoard::<9>::new();
+        OutsideClueEnforcer::new(vec![clue])
+            .enforce_rule(&mut board)
+            .unwrap();
+        // values repeat along a diagonal only between boxes, so 7 is 1 + 2, 1 and 1 + 2.
+        assert_eq!(candidates(&board, 0, 4), [1, 2]);
+        assert_eq!(candidates(&board, 1, 5), [1, 2]);
+        assert_eq!(candidates(&board, 2, 6), [1]);
+        assert_eq!(candidates(&board, 3, 7), [1, 2]);
+        assert_eq!(candidates(&board, 4, 8), [1, 2]);
+    }
+
+    #[test]
+    fn rejects_invalid_clues() {
+        assert!(OutsideClue::row_sandwich(0, 36, 9).is_err());
+        assert!(OutsideClue::row_sandwich(9, 0, 9).is_err());
+        assert!(OutsideClue::little_killer((1, 1), (1, 1), 10, 9).is_err());
+        assert!(OutsideClue::little_killer((0, 8), (1, 1), 10, 9).is_err());
+        assert!(OutsideClue::little_killer((0, 8), (1, -1), 8, 9).is_err());
+        assert!(OutsideClue::little_killer((0, 8), (1, 0), 10, 9).is_err());
+    }
+}
@@ -1,16 +1,17 @@
 use crate::board::SudokuBoard;
 use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
-use crate::region::{get_all_boxes, RegionType};
+use crate::region::RegionType;
 use crate::solver::SudokuRuleEnforcer;
 use crate::subset::Subset;
 use std::collections::HashSet;
 use std::ops::Sub;
 
+#[derive(Clone)]
 pub struct PointingSetEnforcer<const N: usize>
 where
     ForSize<N>: StorageForSize,
 {
-    known_pointing_sets: HashSet<Subset>,
+    known_pointing_sets: HashSet<(RegionType, Subset)>,
 }
 
 fn diff<T>(a: &[T], b: &[T]) -> Vec<T::Output>
@@ -36,6 +37,9 @@ where
     ) -> Vec<Subset> {
         let mut pointing_sets = Vec::new();
         for (line_values, line_positions) in val_lines.into_iter().zip(pos_lines) {
+            if line_positions.is_empty() {
+                continue;
+            }
             let only_in_line: Vec<_> = diff(val_total, &line_values)
                 .into_iter()
                 .enumerate()
@@ -49,38 +53,89 @@ where
         pointing_sets
     }
 
+    /// The values of the region only possible in one of its rows, columns or, for regions that
+    /// aren't boxes, boxes.
     fn get_pointing_sets_in_region(
         board: &SudokuBoard<N>,
-        region: Vec<(usize, usize)>,
+        region: &[(usize, usize)],
+        is_box: bool,
     ) -> Vec<(RegionType, Subset)> {
         let size = board.size();
-        let block_size = board.block_size();
-        let mut pos_rows = vec![Vec::with_capacity(block_size); block_size];
-        let mut pos_cols = vec![Vec::with_capacity(block_size); block_size];
-        let mut val_rows = vec![vec![0; size]; block_size];
-        let mut val_cols = vec![vec![0; size]; block_size];
         let mut val_total = vec![0; size];
+        for &(row, col) in region {
+            for val in board.get_possible_values(row, col) {
+                val_total[val - 1] += 1;
+            }
+        }
 
-        for (row, col) in region {
-            let possible_values = board.get_possible_values(row, col);
-            pos_rows[row % block_size].push((row, col));
-            pos_cols[col % block_size].push((row, col));
+        let mut line_types = vec![RegionType::Row, RegionType::Col];
+        if !is_box {
+            line_types.push(RegionType::Box);
+        }
 
-            for val in possible_values {
-                val_rows[row % block_size][val - 1] += 1;
-                val_cols[col % block_size][val - 1] += 1;
-                val_total[val - 1] += 1;
+        let mut pointing_sets = Vec::new();
+        for line_type in line_types {
+            // indexed by the lines of the whole board, as jigsaw boxes can span any of them.
+            let mut pos_lines = vec![Vec::new(); size];
+            let mut val_lines = vec![vec![0; size]; size];
+            for &(row, col) in region {
+                let line = match line_type {
+                    RegionType::Row => row,
+                    RegionType::Col => col,
+                    _ => board.layout().box_index(row, col),
+                };
+                pos_lines[line].push((row, col));
+                for val in board.get_possible_values(row, col) {
+                    val_lines[line][val - 1] += 1;
+                }
             }
+            pointing_sets.extend(
+                Self::infer_pointing_sets_in_region(pos_lines, val_lines, &val_total)
+                    .into_iter()
+                    .map(|s| (line_type, s)),
+            );
         }
+        pointing_sets
+    }
 
-        let row_pointing_sets = Self::infer_pointing_sets_in_region(pos_rows, val_rows, &val_total)
-            .into_iter()
-            .map(|s| (RegionType::Row, s));
-        let col_pointing_sets = Self::infer_pointing_sets_in_region(pos_cols, val_cols, &val_total)
-            .into_iter()
-            .map(|s| (RegionType::Col, s));
+    /// Removes the value from the cells seeing all of its possible positions in the region,
+    /// which chess constraints make more than the lines and boxes crossing it.
+    fn remove_from_common_peers(
+        board: &mut SudokuBoard<N>,
+        region: &[(usize, usize)],
+        value: usize,
+    ) -> Result<bool, String> {
+        let positions: Vec<_> = region
+            .iter()
+            .copied()
+            .filter(|&(row, col)| board.get_possible_values(row, col).any(|v| v == value))
+            .collect();
+        if positions.len() < 2 {
+            return Ok(false);
+        }
 
-        row_pointing_sets.chain(col_pointing_sets).collect()
+        let layout = board.layout();
+        let common_peers: Vec<_> = layout
+            .peers(positions[0].0, positions[0].1)
+            .filter(|cell| !positions.contains(cell))
+            .filter(|&cell| {
+                positions[1..]
+                    .iter()
+                    .all(|&position| layout.sees(cell, position))
+            })
+            .collect();
+        for (row, col) in common_peers {
+            if board.get_value(row, col).is_some()
+                || !board.get_possible_values(row, col).any(|v| v == value)
+            {
+                continue;
+            }
+            let is_solved = board.remove_value(row, col, value)?;
+            if is_solved {
+                return Ok(true);
+            }
+        }
+        Ok(false)
     }
 }
 
@@ -92,19 +147,41 @@ where
         "PointingSetEnforcer"
     }
     fn enforce_rule(&mut self, board: &mut SudokuBoard<N>) -> Result<bool, String> {
-        let boxes = get_all_boxes(board.size());
+        let boxes = board
+            .layout()
+            .boxes()
+            .iter()
+            .map(|box_| (box_.clone(), true));
+        let extra_regions = board
+            .layout()
+            .extra_regions()
+            .iter()
+            .map(|(_, region)| (region.clone(), false));
+        let regions: Vec<_> = boxes.chain(extra_regions).collect();
 
-        for box_ in boxes {
-            let pointing_sets = Self::get_pointing_sets_in_region(board, box_);
-            for (region_type, subset) in pointing_sets {
-                if self.known_pointing_sets.contains(&subset) {
+        for (region, is_box) in regions {
+            let pointing_sets = Self::get_pointing_sets_in_region(board, &region, is_box);
+            for pointing_set in pointing_sets {
+                if self.known_pointing_sets.contains(&pointing_set) {
                     continue;
                 }
-                let is_solved = board.apply_external_subset(region_type, &subset)?;
+                let (region_type, subset) = &pointing_set;
+                let is_solved = board.apply_external_subset(*region_type, subset)?;
                 if is_solved {
                     return Ok(true);
                 }
-                self.known_pointing_sets.insert(subset);
+                self.known_pointing_sets.insert(pointing_set);
+            }
+        }
+
+        if !board.layout().chess_constraints().is_empty() {
+            for (_, region) in board.regions().iter() {
+                for value in 1..=N {
+                    let is_solved = Self::remove_from_common_peers(board, region, value)?;
+                    if is_solved {
+                        return Ok(true);
+                    }
+                }
             }
         }
 
@@ -0,0 +1,262 @@
+use crate::board::SudokuBoard;
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::solver::hidden_single::HiddenSingleEnforcer;
+use crate::solver::pointing_set::PointingSetEnforcer;
+use crate::solver::sub_set::SubSetEnforcer;
+use crate::solver::x_wing::XWingEnforcer;
+use crate::solver::SudokuRuleEnforcer;
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Score given to puzzles the supported techniques can't finish.
+pub const UNSOLVED_SCORE: f32 = 10.0;
+
+/// Solving techniques, ordered from the cheapest to the most expensive.
+///
+/// Naked singles are resolved by the board itself while propagating a placement,
+/// so they never count as a step.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Technique {
+    HiddenSingle,
+    PointingSet,
+    NakedPair,
+    XWing,
+    NakedTriple,
+    NakedQuad,
+}
+
+impl Technique {
+    pub const ALL: [Technique; 6] = [
+        Technique::HiddenSingle,
+        Technique::PointingSet,
+        Technique::NakedPair,
+        Technique::XWing,
+        Technique::NakedTriple,
+        Technique::NakedQuad,
+    ];
+
+    /// The Sudoku Explainer rating of the technique.
+    pub const fn score(self) -> f32 {
+        match self {
+            Technique::HiddenSingle => 1.5,
+            Technique::PointingSet => 2.6,
+            Technique::NakedPair => 3.0,
+            Technique::XWing => 3.2,
+            Technique::NakedTriple => 3.6,
+            Technique::NakedQuad => 5.0,
+        }
+    }
+
+    fn enforcer<const N: usize>(self) -> Box<dyn SudokuRuleEnforcer<N>>
+    where
+        ForSize<N>: StorageForSize,
+    {
+        match self {
+            Technique::HiddenSingle => Box::new(HiddenSingleEnforcer::<N>::new()),
+            Technique::PointingSet => Box::new(PointingSetEnforcer::<N>::new()),
+            Technique::NakedPair => Box::new(SubSetEnforcer::<N>::with_max_size(2)),
+            Technique::XWing => Box::new(XWingEnforcer::<N>::new()),
+            Technique::NakedTriple => Box::new(SubSetEnforcer::<N>::with_max_size(3)),
+            Technique::NakedQuad => Box::new(SubSetEnforcer::<N>::with_max_size(4)),
+        }
+    }
+}
+
+impl Display for Technique {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Technique::HiddenSingle => "hidden-single",
+            Technique::PointingSet => "pointing-set",
+            Technique::NakedPair => "naked-pair",
+            Technique::XWing => "x-wing",
+            Technique::NakedTriple => "naked-triple",
+            Technique::NakedQuad => "naked-quad",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Technique {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Technique::ALL
+            .into_iter()
+            .find(|technique| technique.to_string() == s)
+            .ok_or_else(|| format!("Unknown technique '{s}'."))
+    }
+}
+
+/// Score buckets of the daily puzzle feed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DifficultyLevel {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl DifficultyLevel {
+    /// The scores of the techniques of the level: singles for easy, pointing sets and naked pairs
+    /// for medium, x-wings and naked triples for hard, and naked quads for expert.
+    pub const fn score_range(self) -> RangeInclusive<f32> {
+        match self {
+            DifficultyLevel::Easy => 0.0..=Technique::HiddenSingle.score(),
+            DifficultyLevel::Medium => {
+                Technique::PointingSet.score()..=Technique::NakedPair.score()
+            }
+            DifficultyLevel::Hard => Technique::XWing.score()..=Technique::NakedTriple.score(),
+            DifficultyLevel::Expert => Technique::NakedQuad.score()..=Technique::NakedQuad.score(),
+        }
+    }
+}
+
+impl FromStr for DifficultyLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(DifficultyLevel::Easy),
+            "medium" => Ok(DifficultyLevel::Medium),
+            "hard" => Ok(DifficultyLevel::Hard),
+            "expert" => Ok(DifficultyLevel::Expert),
+            _ => Err(format!(
+                "Unknown difficulty '{s}', expected one of easy, medium, hard, expert."
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Difficulty {
+    /// The most expensive technique the solve needed, `None` if the givens were enough.
+    pub hardest: Option<Technique>,
+    /// How many passes of a technique over the board made progress. A pass applies the technique
+    /// everywhere it fits, so it may place or remove several values.
+    pub steps: usize,
+    /// Comparable to the Sudoku Explainer rating, `UNSOLVED_SCORE` when not solved.
+    pub score: f32,
+    pub solved: bool,
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let hardest = self
+            .hardest
+            .map_or("none".to_string(), |technique| technique.to_string());
+        write!(
+            f,
+            "score: {:.1}, hardest technique: {hardest}, steps: {}",
+            self.score, self.steps
+        )?;
+        if !self.solved {
+            write!(f, " (not solved with the supported techniques)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Rates a puzzle by always applying the cheapest technique that makes progress.
+pub fn rate<const N: usize>(givens: &[(usize, usize, usize)]) -> Result<Difficulty, String>
+where
+    ForSize<N>: StorageForSize,
+{
+    let mut board = SudokuBoard::<N>::from_givens(givens)?;
+    let mut enforcers: Vec<_> = Technique::ALL
+        .into_iter()
+        .map(|technique| (technique, technique.enforcer::<N>()))
+        .collect();
+
+    let mut hardest = None;
+    let mut steps = 0;
+    while !board.is_solved() {
+        let mut progressed = false;
+        for (technique, enforcer) in &mut enforcers {
+            board.improved.clear();
+            enforcer.enforce_rule(&mut board)?;
+            if !board.improved.is_empty() {
+                hardest = hardest.max(Some(*technique));
+                steps += 1;
+                progressed = true;
+                break;
+            }
+        }
+        if !progressed {
+            break;
+        }
+    }
+
+    let solved = board.is_solved();
+    let score = if solved {
+        hardest.map_or(1.0, Technique::score)
+    } else {
+        UNSOLVED_SCORE
+    };
+    Ok(Difficulty {
+        hardest,
+        steps,
+        score,
+        solved,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rate, Technique, UNSOLVED_SCORE};
+    use crate::puzzle::parse_puzzle;
+
+    fn rate_puzzle(puzzle: &str) -> super::Difficulty {
+        rate::<9>(&parse_puzzle(puzzle).unwrap().1).unwrap()
+    }
+
+    #[test]
+    fn rates_given_puzzles() {
+        let difficulty = rate_puzzle(
+            "534678912672195348198342567859761423426853791713924856961537284287419635345286179",
+        );
+        assert!(difficulty.solved);
+        assert_eq!(difficulty.hardest, None);
+        assert_eq!(difficulty.steps, 0);
+        assert_eq!(difficulty.score, 1.0);
+    }
+
+    #[test]
+    fn rates_by_the_hardest_technique() {
+        let puzzles = [
+            (
+                "..4...9.......534.198...5..8.9.6.4...2.85...1..3.2...6.61.........41........8....",
+                Technique::HiddenSingle,
+            ),
+            (
+                "...6.8........534..9..4......97.1..34......9..1..24..69.....28..8......53..2....9",
+                Technique::PointingSet,
+            ),
+            (
+                "...6..9...7.....4...8.....7..97....342.8.3....1..248....1.3.....8....6.53......7.",
+                Technique::XWing,
+            ),
+            (
+                "4.........6....7.....4289....4.7....7..5.98...95.1..3...37............49....31..6",
+                Technique::NakedQuad,
+            ),
+        ];
+        for (puzzle, technique) in puzzles {
+            let difficulty = rate_puzzle(puzzle);
+            assert!(difficulty.solved, "{puzzle}");
+            assert_eq!(difficulty.hardest, Some(technique));
+            assert_eq!(difficulty.score, technique.score());
+            assert!(difficulty.steps > 0);
+        }
+    }
+
+    #[test]
+    fn rates_unsolved_puzzles() {
+        // AI Escargot needs techniques beyond the supported ones.
+        let difficulty = rate_puzzle(
+            "1....7.9..3..2...8..96..5....53..9...1..8...26....4...3......1..4......7..7...3..",
+        );
+        assert!(!difficulty.solved);
+        assert_eq!(difficulty.score, UNSOLVED_SCORE);
+    }
+}
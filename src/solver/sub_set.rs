@@ -1,13 +1,16 @@
 use crate::board::SudokuBoard;
 use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
-use crate::region::get_all_regions;
 use crate::solver::SudokuRuleEnforcer;
 use crate::subset::Subset;
 use itertools::Itertools;
 use std::collections::HashSet;
 
+#[derive(Clone)]
 pub struct SubSetEnforcer<const N: usize> {
     known_sub_sets: HashSet<Subset>,
+    max_sub_set_size: usize,
+    /// Cells with more candidates can't be part of a subset.
+    max_candidates: usize,
 }
 
 type PositionCombination = Vec<((usize, usize), Vec<usize>)>;
@@ -31,18 +34,30 @@ where
     pub fn new() -> Self {
         Self {
             known_sub_sets: HashSet::default(),
+            max_sub_set_size: N / 2,
+            max_candidates: (N / 2).saturating_sub(1),
+        }
+    }
+
+    /// Only looks for subsets of up to `max_sub_set_size` cells (2 for pairs, 3 for triples...).
+    pub fn with_max_size(max_sub_set_size: usize) -> Self {
+        Self {
+            known_sub_sets: HashSet::default(),
+            max_sub_set_size,
+            max_candidates: max_sub_set_size,
         }
     }
 
     fn get_possible_combinations_in_region(
         board: &SudokuBoard<N>,
-        region: Vec<(usize, usize)>,
+        region: &[(usize, usize)],
+        max_sub_set_size: usize,
+        max_candidates: usize,
     ) -> Vec<PositionCombination> {
-        let max_sub_set_size = board.size() / 2;
         let possible_positions: Vec<_> = region
-            .into_iter()
-            .map(|p| (p, board.get_possible_values(p.0, p.1).collect::<Vec<_>>()))
-            .filter(|(_, pv)| pv.len() > 1 && pv.len() < max_sub_set_size)
+            .iter()
+            .map(|&p| (p, board.get_possible_values(p.0, p.1).collect::<Vec<_>>()))
+            .filter(|(_, pv)| pv.len() > 1 && pv.len() <= max_candidates)
             .collect();
 
         let mut possible_combinations = Vec::new();
@@ -53,8 +68,17 @@ where
         possible_combinations
     }
 
-    fn get_sub_sets_in_region(board: &SudokuBoard<N>, region: Vec<(usize, usize)>) -> Vec<Subset> {
-        let possible_combinations = Self::get_possible_combinations_in_region(board, region);
+    fn get_sub_sets_in_region(
+        &self,
+        board: &SudokuBoard<N>,
+        region: &[(usize, usize)],
+    ) -> Vec<Subset> {
+        let possible_combinations = Self::get_possible_combinations_in_region(
+            board,
+            region,
+            self.max_sub_set_size,
+            self.max_candidates,
+        );
 
         let mut sub_sets = Vec::new();
         for possible_combination in possible_combinations {
@@ -78,15 +102,15 @@ where
     }
 
     fn enforce_rule(&mut self, board: &mut SudokuBoard<N>) -> Result<bool, String> {
-        let regions = get_all_regions(board.size());
+        let regions = board.regions();
 
-        for (region_type, region) in regions {
-            let subsets = Self::get_sub_sets_in_region(board, region);
+        for (region_type, region) in regions.iter() {
+            let subsets = self.get_sub_sets_in_region(board, region);
             for subset in subsets {
                 if self.known_sub_sets.contains(&subset) {
                     continue;
                 }
-                let is_solved = board.apply_external_subset(region_type, &subset)?;
+                let is_solved = board.apply_external_subset(*region_type, &subset)?;
                 if is_solved {
                     return Ok(true);
                 }
@@ -0,0 +1,94 @@
+use crate::board::SudokuBoard;
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::region::RegionType;
+use crate::solver::SudokuRuleEnforcer;
+use crate::subset::Subset;
+use std::collections::HashSet;
+
+/// When a value is limited to the same two columns in two rows, it is removed from the
+/// rest of those columns (and the same with rows and columns swapped).
+#[derive(Clone)]
+pub struct XWingEnforcer<const N: usize> {
+    known_x_wings: HashSet<Subset>,
+}
+
+impl<const N: usize> XWingEnforcer<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    pub fn new() -> Self {
+        Self {
+            known_x_wings: HashSet::default(),
+        }
+    }
+
+    /// Finds the x-wings of `value` in the lines given by `to_position(line, i)`,
+    /// returning a subset for each of the two crossing lines.
+    fn get_x_wings(
+        board: &SudokuBoard<N>,
+        value: usize,
+        to_position: impl Fn(usize, usize) -> (usize, usize),
+    ) -> Vec<Subset> {
+        let size = board.size();
+        let lines: Vec<(usize, Vec<usize>)> = (0..size)
+            .map(|line| {
+                let indices = (0..size)
+                    .filter(|&i| {
+                        let (row, col) = to_position(line, i);
+                        board.get_value(row, col).is_none()
+                            && board.get_possible_values(row, col).any(|v| v == value)
+                    })
+                    .collect();
+                (line, indices)
+            })
+            .filter(|(_, indices): &(usize, Vec<usize>)| indices.len() == 2)
+            .collect();
+
+        let mut x_wings = Vec::new();
+        for (i, (line1, indices1)) in lines.iter().enumerate() {
+            for (line2, indices2) in &lines[i + 1..] {
+                if indices1 != indices2 {
+                    continue;
+                }
+                for &index in indices1 {
+                    let positions = vec![to_position(*line1, index), to_position(*line2, index)];
+                    x_wings.push(Subset::new(vec![value], positions));
+                }
+            }
+        }
+        x_wings
+    }
+}
+
+impl<const N: usize> SudokuRuleEnforcer<N> for XWingEnforcer<N>
+where
+    ForSize<N>: StorageForSize,
+{
+    fn name(&self) -> &'static str {
+        "XWingEnforcer"
+    }
+
+    fn enforce_rule(&mut self, board: &mut SudokuBoard<N>) -> Result<bool, String> {
+        for value in 1..=board.size() {
+            let row_x_wings = Self::get_x_wings(board, value, |line, i| (line, i))
+                .into_iter()
+                .map(|s| (RegionType::Col, s));
+            let col_x_wings = Self::get_x_wings(board, value, |line, i| (i, line))
+                .into_iter()
+                .map(|s| (RegionType::Row, s));
+
+            for (region_type, subset) in row_x_wings.chain(col_x_wings).collect::<Vec<_>>() {
+                if self.known_x_wings.contains(&subset) {
+                    continue;
+                }
+                let is_solved = board.apply_external_subset(region_type, &subset)?;
+                if is_solved {
+                    return Ok(true);
+                }
+                self.known_x_wings.insert(subset);
+            }
+        }
+
+        Ok(false)
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Hash, PartialEq, Eq, Debug)]
+#[derive(Clone, Hash, PartialEq, Eq, Debug)]
 pub struct Subset {
     pub values: Vec<usize>,
     pub positions: Vec<(usize, usize)>,
@@ -0,0 +1,537 @@
+use crate::any_board::{AnyBoard, AnySolver};
+use crate::board::{Parity, SudokuBoard};
+use crate::possibility_matrix::bit_storage::{ForSize, StorageForSize};
+use crate::puzzle::{
+    parse_cell, parse_layout, parse_outside_clues, to_cell_string, to_layout_string,
+    to_puzzle_string,
+};
+use crate::region::{default_box_shape, ChessConstraint, RegionLayout, RegionType};
+use crate::solver::adjacency::{kropki, non_consecutive, Adjacency, Relation};
+use crate::solver::killer_cage::Cage;
+use crate::solver::line::Line;
+use serde::{Deserialize, Serialize};
+
+type Cell = (usize, usize);
+
+/// A puzzle and its variant rules in a JSON file, so they can be shared between tools.
+///
+/// Cells are written like `r1c2` and the givens like a `parse_puzzle` puzzle:
+///
+/// ```json
+/// {
+///   "size": 9,
+///   "givens": "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..",
+///   "pencil_marks": [{ "cell": "r1c1", "values": [4, 5] }],
+///   "rules": ["diagonal", "anti-knight"],
+///   "cages": [{ "sum": 15, "cells": ["r1c1", "r1c2"] }],
+///   "lines": [{ "kind": "thermo", "cells": ["r2c2", "r2c3", "r3c4"] }],
+///   "dots": [{ "kind": "white", "cells": ["r5c5", "r5c6"] }],
+///   "parity": [{ "kind": "odd", "cell": "r9c9" }],
+///   "inequalities": [{ "cells": ["r8c8", "r8c9"] }],
+///   "clues": ["sandwich r3 15", "little-killer r1c3 se 26"]
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VariantPuzzle {
+    pub size: usize,
+    /// The boxes of `default_box_shape` when missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boxes: Option<Boxes>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub givens: String,
+    /// The only values left in some cells.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pencil_marks: Vec<PencilMark>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<Rule>,
+    /// Other regions of `size` cells holding every value once, like a single diagonal.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_regions: Vec<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cages: Vec<CageDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<LineDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dots: Vec<DotDefinition>,
+    /// The odd and even cells.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parity: Vec<ParityDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inequalities: Vec<InequalityDefinition>,
+    /// Sandwich and little killer clues, written like `parse_outside_clues` lines.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clues: Vec<String>,
+}
+
+/// Boxes of `[box_rows, box_cols]` cells, or the jigsaw rows of `parse_layout`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Boxes {
+    Shape([usize; 2]),
+    Jigsaw(Vec<String>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PencilMark {
+    pub cell: String,
+    pub values: Vec<usize>,
+}
+
+/// The variant rules that aren't drawn on the grid.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    Diagonal,
+    Windoku,
+    DisjointGroups,
+    AntiKnight,
+    AntiKing,
+    NonConsecutive,
+    /// Adjacent cells without a dot have neither relation of the dots.
+    NegativeDots,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CageDefinition {
+    pub sum: usize,
+    pub cells: Vec<String>,
+}
+
+/// A line of any kind `parse_lines` reads, the circle first for an arrow.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LineDefinition {
+    pub kind: String,
+    pub cells: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DotDefinition {
+    pub kind: DotKind,
+    pub cells: [String; 2],
+}
+
+/// White dots join consecutive values, black dots a value and its double.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DotKind {
+    White,
+    Black,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParityDefinition {
+    pub kind: ParityKind,
+    pub cell: String,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParityKind {
+    Odd,
+    Even,
+}
+
+/// A greater-than sign between adjacent cells, the first one holding the greater value.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InequalityDefinition {
+    pub cells: [String; 2],
+}
+
+impl VariantPuzzle {
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        serde_json::from_str(text).map_err(|err| format!("Invalid variant puzzle: {err}."))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("A variant puzzle always serializes.")
+    }
+
+    /// The layout, solved cells and pencil marks of the board. The cages, lines, dots, signs and
+    /// outside clues are enforced by a solver rather than the board, so they are left to the
+    /// caller.
+    pub fn from_board<const N: usize>(board: &SudokuBoard<N>) -> Self
+    where
+        ForSize<N>: StorageForSize,
+    {
+        let layout = board.layout();
+        let boxes = match layout.box_shape() {
+            Some(shape) if shape == default_box_shape(N) => None,
+            Some((box_rows, box_cols)) => Some(Boxes::Shape([box_rows, box_cols])),
+            None => Some(Boxes::Jigsaw(
+                to_layout_string(layout)
+                    .lines()
+                    .map(str::to_string)
+                    .collect(),
+            )),
+        };
+
+        let mut rules = Vec::new();
+        let mut extra_regions = Vec::new();
+        for (region_type, region) in layout.extra_regions() {
+            let rule = match region_type {
+                RegionType::Diagonal => Rule::Diagonal,
+                RegionType::DisjointGroup => Rule::DisjointGroups,
+                RegionType::Window => Rule::Windoku,
+                _ => {
+                    extra_regions.push(region.iter().copied().map(to_cell_string).collect());
+                    continue;
+                }
+            };
+            if !rules.contains(&rule) {
+                rules.push(rule);
+            }
+        }
+        rules.extend(
+            layout
+                .chess_constraints()
+                .iter()
+                .map(|constraint| match constraint {
+                    ChessConstraint::AntiKnight => Rule::AntiKnight,
+                    ChessConstraint::AntiKing => Rule::AntiKing,
+                }),
+        );
+
+        let cells = (0..N).flat_map(|row| (0..N).map(move |col| (row, col)));
+        let givens: Vec<_> = cells
+            .clone()
+            .filter_map(|(row, col)| board.get_value(row, col).map(|value| (row, col, value)))
+            .collect();
+        let pencil_marks = cells
+            .filter(|&(row, col)| board.get_value(row, col).is_none())
+            .map(|(row, col)| {
+                (
+                    row,
+                    col,
+                    board.get_possible_values(row, col).collect::<Vec<_>>(),
+                )
+            })
+            .filter(|(_, _, values)| values.len() < N)
+            .map(|(row, col, values)| PencilMark {
+                cell: to_cell_string((row, col)),
+                values,
+            })
+            .collect();
+
+        Self {
+            size: N,
+            boxes,
+            givens: to_puzzle_string(N, &givens),
+            pencil_marks,
+            rules,
+            extra_regions,
+            ..Self::default()
+        }
+    }
+
+    /// The puzzle with the cells and pencil marks of `board`, like a solved board, keeping its
+    /// rules, cages, lines, dots, signs and outside clues.
+    pub fn with_board(&self, board: &AnyBoard) -> Self {
+        Self {
+            rules: self.rules.clone(),
+            cages: self.cages.clone(),
+            lines: self.lines.clone(),
+            dots: self.dots.clone(),
+            parity: self.parity.clone(),
+            inequalities: self.inequalities.clone(),
+            clues: self.clues.clone(),
+            ..board.to_variant()
+        }
+    }
+
+    fn cells(&self, cells: &[String]) -> Result<Vec<Cell>, String> {
+        cells
+            .iter()
+            .map(|cell| parse_cell(cell, self.size))
+            .collect()
+    }
+
+    pub fn layout(&self) -> Result<RegionLayout, String> {
+        let mut layout = match &self.boxes {
+            None => RegionLayout::for_size(self.size),
+            Some(Boxes::Shape([box_rows, box_cols])) => {
+                let (box_rows, box_cols) = (*box_rows, *box_cols);
+                if box_rows * box_cols != self.size {
+                    return Err(format!(
+                        "Boxes of {box_rows}x{box_cols} cells don't fit a board of size {}.",
+                        self.size
+                    ));
+                }
+                RegionLayout::with_box_shape(box_rows, box_cols)
+            }
+            Some(Boxes::Jigsaw(rows)) => parse_layout(&rows.join("\n"))?,
+        };
+        if layout.size() != self.size {
+            return Err(format!(
+                "The boxes are for size {} but the board is of size {}.",
+                layout.size(),
+                self.size
+            ));
+        }
+
+        for rule in &self.rules {
+            layout = match rule {
+                Rule::Diagonal => layout.with_diagonals(),
+                Rule::Windoku => layout.with_windows()?,
+                Rule::DisjointGroups => layout.with_disjoint_groups()?,
+                Rule::AntiKnight => layout.with_chess_constraint(ChessConstraint::AntiKnight),
+                Rule::AntiKing => layout.with_chess_constraint(ChessConstraint::AntiKing),
+                Rule::NonConsecutive | Rule::NegativeDots => layout,
+            };
+        }
+        for region in &self.extra_regions {
+            layout = layout.with_extra_region(self.cells(region)?)?;
+        }
+        Ok(layout)
+    }
+
+    /// The board of the layout with the givens set and the pencil marks applied.
+    pub fn board(&self) -> Result<AnyBoard, String> {
+        let mut board = if self.givens.is_empty() {
+            AnyBoard::with_layout(self.layout()?)?
+        } else {
+            AnyBoard::from_puzzle(&self.givens, self.layout()?)?
+        };
+        for mark in &self.pencil_marks {
+            let (row, col) = parse_cell(&mark.cell, self.size)?;
+            if let Some(&value) = mark.values.iter().find(|&&v| v == 0 || v > self.size) {
+                return Err(format!(
+                    "Invalid pencil mark {value} in {}, expected between 1 and {}.",
+                    mark.cell, self.size
+                ));
+            }
+            board.retain_values(row, col, &mark.values)?;
+        }
+        for cell in &self.parity {
+            let (row, col) = parse_cell(&cell.cell, self.size)?;
+            let parity = match cell.kind {
+                ParityKind::Odd => Parity::Odd,
+                ParityKind::Even => Parity::Even,
+            };
+            board.constrain_parity(row, col, parity)?;
+        }
+        Ok(board)
+    }
+
+    /// A solver of the board enforcing the cages, lines, dots, signs and outside clues, guessing
+    /// when they are stuck.
+    pub fn solver(&self) -> Result<AnySolver, String> {
+        let mut solver = AnySolver::from_board(self.board()?);
+        let mut search = false;
+        if !self.cages.is_empty() {
+            let cages = self
+                .cages
+                .iter()
+                .map(|cage| Cage::new(cage.sum, self.cells(&cage.cells)?, self.size))
+                .collect::<Result<_, _>>()?;
+            solver = solver.with_cages(cages);
+            search = true;
+        }
+
+        let dots = self
+            .dots
+            .iter()
+            .map(|dot| {
+                let relation = match dot.kind {
+                    DotKind::White => Relation::Consecutive,
+                    DotKind::Black => Relation::Double,
+                };
+                let [first, second] = &dot.cells;
+                Adjacency::new(
+                    parse_cell(first, self.size)?,
+                    parse_cell(second, self.size)?,
+                    relation,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut adjacencies = kropki(dots, self.rules.contains(&Rule::NegativeDots), self.size);
+        if self.rules.contains(&Rule::NonConsecutive) {
+            adjacencies.extend(non_consecutive(self.size));
+        }
+        for sign in &self.inequalities {
+            let [greater, less] = &sign.cells;
+            adjacencies.push(Adjacency::new(
+                parse_cell(greater, self.size)?,
+                parse_cell(less, self.size)?,
+                Relation::Greater,
+            )?);
+        }
+        if !adjacencies.is_empty() {
+            solver = solver.with_adjacencies(adjacencies);
+            search = true;
+        }
+
+        if !self.lines.is_empty() {
+            let lines = self
+                .lines
+                .iter()
+                .map(|line| Line::from_kind(&line.kind, self.cells(&line.cells)?, self.size))
+                .collect::<Result<_, _>>()?;
+            solver = solver.with_lines(lines);
+            search = true;
+        }
+        if !self.clues.is_empty() {
+            let clues = parse_outside_clues(&self.clues.join("\n"), self.size)?;
+            solver = solver.with_outside_clues(clues);
+            search = true;
+        }
+        if search {
+            solver = solver.with_search();
+        }
+        Ok(solver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Boxes, ParityKind, Rule, VariantPuzzle};
+    use crate::region::RegionType;
+
+    const PUZZLE: &str = r#"{
+        "size": 4,
+        "givens": "1...............",
+        "pencil_marks": [{ "cell": "r4c1", "values": [4] }],
+        "cages": [{ "sum": 3, "cells": ["r2c3", "r2c4"] }],
+        "lines": [
+            { "kind": "thermo", "cells": ["r3c2", "r3c1"] },
+            { "kind": "v", "cells": ["r2c4", "r3c4"] }
+        ],
+        "dots": [
+            { "kind": "white", "cells": ["r1c3", "r1c4"] },
+            { "kind": "black", "cells": ["r3c3", "r4c3"] }
+        ]
+    }"#;
+
+    #[test]
+    fn solves_variant_puzzle() {
+        let puzzle = VariantPuzzle::from_json(PUZZLE).unwrap();
+        assert_eq!(puzzle.lines[1].kind, "v");
+        let solved = puzzle.solver().unwrap().solve().unwrap();
+        assert!(solved.is_solved());
+        assert_eq!(solved.to_variant().boxes, None);
+        assert_eq!(solved.to_variant().givens, "1234341221434321");
+    }
+
+    #[test]
+    fn solves_parity_signs_and_clues() {
+        let text = r#"{
+            "size": 4,
+            "parity": [
+                { "kind": "even", "cell": "r1c2" },
+                { "kind": "even", "cell": "r2c2" },
+                { "kind": "odd", "cell": "r4c4" }
+            ],
+            "inequalities": [{ "cells": ["r3c3", "r3c4"] }, { "cells": ["r4c1", "r4c2"] }],
+            "clues": ["sandwich r1 5", "sandwich c1 5", "little-killer r1c2 se 6"]
+        }"#;
+        let puzzle = VariantPuzzle::from_json(text).unwrap();
+        assert_eq!(puzzle.parity[2].kind, ParityKind::Odd);
+        assert_eq!(VariantPuzzle::from_json(&puzzle.to_json()).unwrap(), puzzle);
+
+        let solved = puzzle.solver().unwrap().solve().unwrap();
+        assert!(solved.is_solved());
+        assert_eq!(solved.to_variant().givens, "1234341221434321");
+    }
+
+    #[test]
+    fn writes_boards_back() {
+        let text = r#"{
+            "size": 6,
+            "boxes": [3, 2],
+            "givens": "1...................................",
+            "pencil_marks": [{ "cell": "r6c6", "values": [2, 3] }],
+            "rules": ["disjoint-groups", "anti-king"],
+            "extra_regions": [["r1c6", "r2c5", "r3c4", "r4c3", "r5c2", "r6c1"]]
+        }"#;
+        let puzzle = VariantPuzzle::from_json(text).unwrap();
+        let board = puzzle.board().unwrap();
+        let written = board.to_variant();
+        assert_eq!(written.boxes, Some(Boxes::Shape([3, 2])));
+        assert_eq!(written.rules, [Rule::DisjointGroups, Rule::AntiKing]);
+        assert_eq!(written.extra_regions, puzzle.extra_regions);
+        assert!(written.pencil_marks.iter().any(|mark| mark.cell == "r6c6"));
+
+        let read = VariantPuzzle::from_json(&written.to_json()).unwrap();
+        assert_eq!(read, written);
+        assert_eq!(read.board().unwrap().to_string(), board.to_string());
+        let layout = read.layout().unwrap();
+        assert_eq!(layout.extra_regions().len(), 7);
+        assert_eq!(layout.extra_regions()[6].0, RegionType::Extra);
+    }
+
+    #[test]
+    fn writes_windoku_puzzles_back() {
+        let puzzle = VariantPuzzle {
+            size: 9,
+            givens:
+                ".6.....4.........1...95.7.84..1...87....4.6...........8...9...23....8..4........."
+                    .to_string(),
+            rules: vec![Rule::Windoku],
+            ..VariantPuzzle::default()
+        };
+        let solved = puzzle.solver().unwrap().solve().unwrap();
+        assert!(solved.is_solved());
+        assert_eq!(solved.to_variant().rules, [Rule::Windoku]);
+
+        // the windows are written as the rule only, so reading them back doesn't add them twice.
+        let written = puzzle.with_board(&solved);
+        assert!(written.extra_regions.is_empty());
+        let read = VariantPuzzle::from_json(&written.to_json()).unwrap();
+        assert_eq!(read, written);
+        assert_eq!(read.layout().unwrap().extra_regions().len(), 4);
+        assert_eq!(read.board().unwrap().to_string(), solved.to_string());
+    }
+
+    #[test]
+    fn writes_jigsaw_boxes() {
+        let puzzle = VariantPuzzle {
+            size: 4,
+            boxes: Some(Boxes::Jigsaw(vec![
+                "AAAB".to_string(),
+                "ACBB".to_string(),
+                "CCDB".to_string(),
+                "CDDD".to_string(),
+            ])),
+            ..VariantPuzzle::default()
+        };
+        let written = puzzle.board().unwrap().to_variant();
+        assert_eq!(
+            written.boxes,
+            Some(Boxes::Jigsaw(vec![
+                "1112".to_string(),
+                "1322".to_string(),
+                "3342".to_string(),
+                "3444".to_string(),
+            ]))
+        );
+        assert_eq!(written.givens, ".".repeat(16));
+    }
+
+    #[test]
+    fn rejects_invalid_puzzles() {
+        assert!(VariantPuzzle::from_json(r#"{ "size": 9, "colour": "red" }"#).is_err());
+        assert!(VariantPuzzle::from_json(r#"{ "size": 9, "rules": ["anti-queen"] }"#).is_err());
+        let invalid = [
+            r#"{ "size": 9, "boxes": [2, 3] }"#,
+            r#"{ "size": 4, "givens": "1" }"#,
+            r#"{ "size": 4, "pencil_marks": [{ "cell": "r1c1", "values": [5] }] }"#,
+            r#"{ "size": 4, "extra_regions": [["r1c1", "r1c2"]] }"#,
+            r#"{ "size": 4, "cages": [{ "sum": 3, "cells": ["r5c1"] }] }"#,
+            r#"{ "size": 4, "lines": [{ "kind": "zipper", "cells": ["r1c1", "r1c2"] }] }"#,
+            r#"{ "size": 4, "dots": [{ "kind": "white", "cells": ["r1c1", "r2c2"] }] }"#,
+            r#"{ "size": 4, "parity": [{ "kind": "odd", "cell": "r5c1" }] }"#,
+            r#"{ "size": 4, "inequalities": [{ "cells": ["r1c1", "r1c3"] }] }"#,
+            r#"{ "size": 4, "clues": ["sandwich r5 3"] }"#,
+        ];
+        for text in invalid {
+            let puzzle = VariantPuzzle::from_json(text).unwrap();
+            assert!(puzzle.solver().is_err(), "{text}");
+        }
+    }
+}